@@ -0,0 +1,59 @@
+use super::{Attributes, Skills};
+use std::cmp::max;
+
+pub const BASE_DEFENSE: i32 = 10;
+pub const HP_PER_FITNESS: i32 = 5;
+pub const SPEED_PER_QUICKNESS: i32 = 10;
+pub const BASE_CARRY: i32 = 10;
+pub const CARRY_PER_MIGHT: i32 = 2;
+
+// An attribute of 10 is average; every two points above or below that
+// is worth +1 or -1.
+pub fn attr_bonus(value: i32) -> i32 {
+    return (value - 10).div_euclid(2);
+}
+
+// Entities without attributes or skills count as average and untrained.
+pub fn might_bonus(attrs: Option<&Attributes>) -> i32 {
+    return attrs.map_or(0, |a| attr_bonus(a.might));
+}
+
+pub fn fitness_bonus(attrs: Option<&Attributes>) -> i32 {
+    return attrs.map_or(0, |a| attr_bonus(a.fitness));
+}
+
+pub fn quickness_bonus(attrs: Option<&Attributes>) -> i32 {
+    return attrs.map_or(0, |a| attr_bonus(a.quickness));
+}
+
+pub fn intelligence_bonus(attrs: Option<&Attributes>) -> i32 {
+    return attrs.map_or(0, |a| attr_bonus(a.intelligence));
+}
+
+pub fn melee_to_hit(attrs: Option<&Attributes>, skills: Option<&Skills>) -> i32 {
+    return might_bonus(attrs) + skills.map_or(0, |s| s.melee);
+}
+
+pub fn melee_damage(attrs: Option<&Attributes>) -> i32 {
+    return might_bonus(attrs);
+}
+
+pub fn defense(attrs: Option<&Attributes>, skills: Option<&Skills>) -> i32 {
+    return quickness_bonus(attrs) + skills.map_or(0, |s| s.defense);
+}
+
+pub fn healing(attrs: Option<&Attributes>, skills: Option<&Skills>) -> i32 {
+    return intelligence_bonus(attrs) + skills.map_or(0, |s| s.magic);
+}
+
+pub fn max_hp(base_hp: i32, attrs: Option<&Attributes>) -> i32 {
+    return max(1, base_hp + HP_PER_FITNESS * fitness_bonus(attrs));
+}
+
+pub fn initiative_speed(base_speed: i32, attrs: Option<&Attributes>) -> i32 {
+    return max(1, base_speed + SPEED_PER_QUICKNESS * quickness_bonus(attrs));
+}
+
+pub fn carry_capacity(attrs: Option<&Attributes>) -> i32 {
+    return max(1, BASE_CARRY + CARRY_PER_MIGHT * might_bonus(attrs));
+}
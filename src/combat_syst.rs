@@ -1,5 +1,7 @@
+use super::attributes::{defense, melee_damage, melee_to_hit, BASE_DEFENSE};
 use super::components::*;
-use super::GameLog;
+use super::{GameLog, GameStats};
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 use std::cmp::max;
 
@@ -9,46 +11,122 @@ impl<'a> System<'a> for CombatSyst {
     type SystemData = (
         Entities<'a>,
         WriteExpect<'a, GameLog>,
+        WriteExpect<'a, GameStats>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadExpect<'a, Entity>,
         WriteStorage<'a, MeleeMessage>,
         WriteStorage<'a, DamageMessage>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, MeleeBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut log, mut melee_msg, mut damage_msg, names, combat_stats) = data;
+        let (
+            entities,
+            mut log,
+            mut game_stats,
+            mut rng,
+            player,
+            mut melee_msg,
+            mut damage_msg,
+            names,
+            combat_stats,
+            equipped,
+            melee_bonus,
+            defense_bonus,
+            attributes,
+            skills,
+        ) = data;
 
-        for (_entity, melee, name, stats) in (&entities, &melee_msg, &names, &combat_stats).join() {
+        for (entity, melee, name, stats) in (&entities, &melee_msg, &names, &combat_stats).join() {
             if stats.cur_hp > 0 {
                 let target_stats = combat_stats.get(melee.target).unwrap();
                 if target_stats.cur_hp > 0 {
                     let target_name = names.get(melee.target).unwrap();
-                    let damage = max(0, stats.atk - target_stats.def);
 
-                    if damage == 0 {
+                    let mut atk_bonus = 0;
+                    for (gear, bonus) in (&equipped, &melee_bonus).join() {
+                        if gear.owner == entity {
+                            atk_bonus += bonus.atk;
+                        }
+                    }
+
+                    let mut def_bonus = 0;
+                    for (gear, bonus) in (&equipped, &defense_bonus).join() {
+                        if gear.owner == melee.target {
+                            def_bonus += bonus.def;
+                        }
+                    }
+
+                    // Roll a d20 against the target's defense. A natural 1 always
+                    // misses and a natural 20 always hits for double dice.
+                    let natural = rng.roll_dice(1, 20);
+                    let to_hit = natural
+                        + stats.atk
+                        + melee_to_hit(attributes.get(entity), skills.get(entity))
+                        + atk_bonus;
+                    let target_defense = BASE_DEFENSE
+                        + target_stats.def
+                        + defense(attributes.get(melee.target), skills.get(melee.target))
+                        + def_bonus;
+
+                    if natural == 1 {
+                        log.add_message(format!(
+                            "{} swings wildly and misses {}.",
+                            &name.name, &target_name.name
+                        ));
+                        continue;
+                    }
+                    if natural < 20 && to_hit < target_defense {
                         log.add_message(format!(
-                            "{} is unable to hurt {}.",
+                            "{} misses {}.",
                             &name.name, &target_name.name
                         ));
+                        continue;
+                    }
+
+                    // Weapon bonuses only improve the to-hit roll, mirroring armor,
+                    // which only raises defense.
+                    let mut damage =
+                        stats.damage.roll(&mut rng) + melee_damage(attributes.get(entity));
+                    if natural == 20 {
+                        damage += stats.damage.roll(&mut rng) - stats.damage.bonus;
+                    }
+                    let damage = max(1, damage);
+
+                    if natural == 20 {
+                        log.add_message(format!(
+                            "{} critically hits {} for {} hp!",
+                            &name.name, &target_name.name, damage
+                        ));
                     } else {
                         log.add_message(format!(
-                            "{} deals {} hp damage to {}.",
-                            &name.name, damage, &target_name.name
+                            "{} hits {} for {} hp.",
+                            &name.name, &target_name.name, damage
                         ));
+                    }
 
-                        let temp = damage_msg.get_mut(melee.target);
-                        match temp {
-                            None => {
-                                damage_msg
-                                    .insert(melee.target, DamageMessage { val: damage })
-                                    .expect("Unable to insert damage message.");
-                            }
-
-                            Some(msg) => {
-                                msg.val += damage;
-                            }
-                        };
+                    if melee.target == *player {
+                        game_stats.last_attacker = Some(name.name.clone());
                     }
+
+                    let temp = damage_msg.get_mut(melee.target);
+                    match temp {
+                        None => {
+                            damage_msg
+                                .insert(melee.target, DamageMessage { val: damage })
+                                .expect("Unable to insert damage message.");
+                        }
+
+                        Some(msg) => {
+                            msg.val += damage;
+                        }
+                    };
                 }
             }
         }
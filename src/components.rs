@@ -1,79 +1,203 @@
+use super::{Dice, DungeonMaster, GameLog, GameStats, Map, RunState};
 use rltk::{Point, RGB};
+use serde::{Deserialize, Serialize};
+use specs::error::NoError;
 use specs::prelude::*;
+use specs::saveload::{ConvertSaveload, Marker, SimpleMarker, SimpleMarkerAllocator};
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Position {
     pub pt: Point,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct OtherLevelPosition {
+    pub pt: Point,
+    pub depth: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Renderable {
     pub glyph: u8,
     pub fg: RGB,
     pub bg: RGB,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Name {
     pub name: String,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
 #[storage(NullStorage)]
 pub struct Player {}
 
-#[derive(Component, Default)]
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
 #[storage(NullStorage)]
 pub struct Monster {}
 
-#[derive(Component, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct CombatStats {
-    pub max_hp: i32,
+    pub base_hp: i32,
     pub cur_hp: i32,
     pub atk: i32,
     pub def: i32,
+    pub damage: Dice,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct MeleeMessage {
     pub target: Entity,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct DamageMessage {
     pub val: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Viewshed {
     pub visible_tiles: Vec<Point>,
     pub range: i32,
     pub dirty: bool,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
 #[storage(NullStorage)]
 pub struct BlocksTile {}
 
-#[derive(Component, Default)]
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
 #[storage(NullStorage)]
 pub struct BlocksSight {}
 
-#[derive(Component, Debug)]
+#[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct InBackpack {
     pub owner: Entity,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, ConvertSaveload, Clone, Debug)]
 pub struct ItemPickupMessage {
     pub collected_by : Entity,
     pub item : Entity
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
 #[storage(NullStorage)]
 pub struct Item {}
 
-#[derive(Component, Default)]
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct UseItemMessage {
+    pub item: Entity,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct DropItemMessage {
+    pub item: Entity,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Potion {
+    pub heal_amount: i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum EquipmentSlot {
+    Weapon,
+    Armor,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct MeleeBonus {
+    pub atk: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct DefenseBonus {
+    pub def: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Attributes {
+    pub might: i32,
+    pub fitness: i32,
+    pub quickness: i32,
+    pub intelligence: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Skills {
+    pub melee: i32,
+    pub defense: i32,
+    pub magic: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Door {
+    pub open: bool,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Initiative {
+    pub base_speed: i32,
+    pub energy: i32,
+}
+
+#[derive(Component, Clone, Default)]
 #[storage(NullStorage)]
-pub struct Potion {}
+pub struct MyTurn {}
+
+pub struct SerializeMe;
+
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct SerializationHelper {
+    pub map: Map,
+    pub log: GameLog,
+    pub stats: GameStats,
+    pub dungeon: DungeonMaster,
+    pub runstate: RunState,
+}
+
+pub fn register_components(ecs: &mut World) {
+    ecs.register::<Position>();
+    ecs.register::<OtherLevelPosition>();
+    ecs.register::<Renderable>();
+    ecs.register::<Name>();
+    ecs.register::<Player>();
+    ecs.register::<Monster>();
+    ecs.register::<CombatStats>();
+    ecs.register::<MeleeMessage>();
+    ecs.register::<DamageMessage>();
+    ecs.register::<Viewshed>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<BlocksSight>();
+    ecs.register::<Item>();
+    ecs.register::<Potion>();
+    ecs.register::<ItemPickupMessage>();
+    ecs.register::<InBackpack>();
+    ecs.register::<UseItemMessage>();
+    ecs.register::<DropItemMessage>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<MeleeBonus>();
+    ecs.register::<DefenseBonus>();
+    ecs.register::<Attributes>();
+    ecs.register::<Skills>();
+    ecs.register::<Door>();
+    ecs.register::<Initiative>();
+    ecs.register::<MyTurn>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+}
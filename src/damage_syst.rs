@@ -1,5 +1,5 @@
 use super::components::*;
-use super::GameLog;
+use super::{GameLog, GameStats, RunState};
 use specs::prelude::*;
 
 pub struct DamageSyst {}
@@ -7,8 +7,12 @@ pub struct DamageSyst {}
 impl DamageSyst {
     pub fn delete_the_dead(ecs: &mut World) {
         let mut dead_vec: Vec<Entity> = Vec::new();
+        let mut player_dead = false;
 
         let mut log = ecs.write_resource::<GameLog>();
+        let mut game_stats = ecs.write_resource::<GameStats>();
+        let mut runstate = ecs.write_resource::<RunState>();
+        let monsters = ecs.read_storage::<Monster>();
         let combat_stats = ecs.read_storage::<CombatStats>();
         let names = ecs.read_storage::<Name>();
         let players = ecs.read_storage::<Player>();
@@ -20,18 +24,27 @@ impl DamageSyst {
                     None => {
                         let victim_name = names.get(entity).unwrap();
                         log.add_message(format!("{} is dead!", &victim_name.name));
+                        if monsters.get(entity).is_some() {
+                            game_stats.kills += 1;
+                        }
                         dead_vec.push(entity);
                     }
-                    Some(_) => {
-                        log.add_message(format!("You are dead!"));
-                    }
+                    Some(_) => player_dead = true,
                 }
             }
         }
+
+        if player_dead && *runstate != RunState::GameOver {
+            log.add_message(format!("You are dead!"));
+            *runstate = RunState::GameOver;
+        }
         std::mem::drop(entities);
         std::mem::drop(players);
         std::mem::drop(combat_stats);
         std::mem::drop(names);
+        std::mem::drop(monsters);
+        std::mem::drop(runstate);
+        std::mem::drop(game_stats);
         std::mem::drop(log);
 
         for victim in dead_vec {
@@ -0,0 +1,118 @@
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+
+const MAX_DICE: i32 = 100;
+const MAX_SIDES: i32 = 1000;
+const MAX_BONUS: i32 = 1000;
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Dice {
+    pub n_dice: i32,
+    pub die_type: i32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    pub fn new(n_dice: i32, die_type: i32, bonus: i32) -> Dice {
+        return Dice {
+            n_dice,
+            die_type,
+            bonus,
+        };
+    }
+
+    // Parses notation such as "2d6", "1d8+2" or "1d4-1".
+    pub fn parse(notation: &str) -> Result<Dice, String> {
+        let invalid = || format!("Invalid dice '{}': expected notation like 1d6+2", notation);
+        let text = notation.trim();
+
+        let d = text.find('d').ok_or_else(invalid)?;
+        let n_dice = parse_number(&text[..d]).ok_or_else(invalid)?;
+
+        let rest = &text[d + 1..];
+        let (die_text, bonus) = match rest.find(|c| c == '+' || c == '-') {
+            None => (rest, 0),
+            Some(pos) => {
+                let bonus = parse_number(&rest[pos + 1..]).ok_or_else(invalid)?;
+                if &rest[pos..pos + 1] == "-" {
+                    (&rest[..pos], -bonus)
+                } else {
+                    (&rest[..pos], bonus)
+                }
+            }
+        };
+        let die_type = parse_number(die_text).ok_or_else(invalid)?;
+
+        if n_dice < 1 || die_type < 1 {
+            return Err(format!(
+                "Invalid dice '{}': need at least one die with at least one side",
+                notation
+            ));
+        }
+        if n_dice > MAX_DICE || die_type > MAX_SIDES || bonus.abs() > MAX_BONUS {
+            return Err(format!(
+                "Invalid dice '{}': at most {}d{} with a bonus of up to {}",
+                notation, MAX_DICE, MAX_SIDES, MAX_BONUS
+            ));
+        }
+
+        return Ok(Dice::new(n_dice, die_type, bonus));
+    }
+
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> i32 {
+        return rng.roll_dice(self.n_dice, self.die_type) + self.bonus;
+    }
+}
+
+// Accepts plain digits only, so signs cannot sneak in after the operator.
+fn parse_number(text: &str) -> Option<i32> {
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    return text.parse::<i32>().ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_notation() {
+        assert_eq!(Dice::parse("3d4"), Ok(Dice::new(3, 4, 0)));
+        assert_eq!(Dice::parse("1d6+2"), Ok(Dice::new(1, 6, 2)));
+        assert_eq!(Dice::parse(" 2d8-1 "), Ok(Dice::new(2, 8, -1)));
+        assert_eq!(Dice::parse("100d1000+1000"), Ok(Dice::new(100, 1000, 1000)));
+    }
+
+    #[test]
+    fn rejects_malformed_notation() {
+        for notation in ["", "d6", "1d", "1x6", "1d6+", "1d6+x", "1d6 + 2", "1.5d6"].iter() {
+            assert!(Dice::parse(notation).is_err(), "accepted '{}'", notation);
+        }
+    }
+
+    #[test]
+    fn rejects_signed_numbers() {
+        for notation in ["1d6+-2", "1d6-+2", "1d6--2", "+1d6", "-1d6", "1d+6", "1d-6"].iter() {
+            assert!(Dice::parse(notation).is_err(), "accepted '{}'", notation);
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_counts() {
+        for notation in ["0d6", "1d0", "101d6", "1d1001", "1d6+1001", "99999999d99999999"].iter() {
+            assert!(Dice::parse(notation).is_err(), "accepted '{}'", notation);
+        }
+        assert!(Dice::parse("99999999999d6").is_err());
+    }
+
+    #[test]
+    fn rolls_stay_in_range() {
+        let mut rng = RandomNumberGenerator::seeded(7);
+        let dice = Dice::new(2, 6, -1);
+        for _ in 0..1000 {
+            let roll = dice.roll(&mut rng);
+            assert!(roll >= 1 && roll <= 11, "rolled {}", roll);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::components::*;
+use super::map::*;
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct DungeonMaster {
+    maps: HashMap<i32, Map>,
+    pub seed: u64,
+}
+
+impl DungeonMaster {
+    pub fn new(seed: u64) -> DungeonMaster {
+        return DungeonMaster {
+            maps: HashMap::new(),
+            seed,
+        };
+    }
+
+    pub fn level_rng(&self, depth: i32) -> RandomNumberGenerator {
+        let level_seed = self
+            .seed
+            .wrapping_add((depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        return RandomNumberGenerator::seeded(level_seed);
+    }
+
+    pub fn store_map(&mut self, map: &Map) {
+        let mut snapshot = map.clone();
+        snapshot.tile_content.clear();
+        snapshot.opaque_tiles.clear();
+        self.maps.insert(map.depth, snapshot);
+    }
+
+    pub fn get_map(&self, depth: i32) -> Option<Map> {
+        let mut map = self.maps.get(&depth).cloned()?;
+        map.tile_content = vec![Vec::new(); map.num_tiles as usize];
+        map.populate_opaque();
+        return Some(map);
+    }
+}
+
+pub fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    return (nanos % u32::max_value() as u128) as u64;
+}
+
+pub fn freeze_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let player_entity = *ecs.fetch::<Entity>();
+    let depth = ecs.fetch::<Map>().depth;
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level = ecs.write_storage::<OtherLevelPosition>();
+
+    let mut frozen: Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &positions).join() {
+        if entity != player_entity {
+            other_level
+                .insert(entity, OtherLevelPosition { pt: pos.pt, depth })
+                .expect("Unable to stash entity position.");
+            frozen.push(entity);
+        }
+    }
+
+    for entity in frozen {
+        positions.remove(entity);
+    }
+}
+
+pub fn thaw_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let depth = ecs.fetch::<Map>().depth;
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level = ecs.write_storage::<OtherLevelPosition>();
+
+    let mut thawed: Vec<Entity> = Vec::new();
+    for (entity, stash) in (&entities, &other_level).join() {
+        if stash.depth == depth {
+            positions
+                .insert(entity, Position { pt: stash.pt })
+                .expect("Unable to restore entity position.");
+            thawed.push(entity);
+        }
+    }
+
+    for entity in thawed {
+        other_level.remove(entity);
+    }
+}
@@ -1,9 +1,27 @@
+use super::attributes::max_hp;
 use super::components::*;
-use super::util::*;
-use rltk::{Point, RandomNumberGenerator, RGB};
+use super::dice::Dice;
+use super::initiative_syst::NORMAL_SPEED;
+use super::map::*;
+use super::map_builders::rect_tiles;
+use super::raws::*;
+use super::util::Rect;
+use rltk::{Algorithm2D, Point, RandomNumberGenerator, RGB};
 use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use std::cmp::{max, min};
+
+const PLAYER_BASE_HP: i32 = 25;
 
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
+    let attributes = Attributes {
+        might: 12,
+        fitness: 12,
+        quickness: 11,
+        intelligence: 10,
+    };
+    let hp = max_hp(PLAYER_BASE_HP, Some(&attributes));
+
     return ecs
         .create_entity()
         .with(Position {
@@ -24,103 +42,83 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
             dirty: true,
         })
         .with(CombatStats {
-            max_hp: 30,
-            cur_hp: 30,
-            atk: 5,
-            def: 3,
+            base_hp: PLAYER_BASE_HP,
+            cur_hp: hp,
+            atk: 3,
+            def: 2,
+            damage: Dice::new(1, 6, 0),
         })
-        .build();
-}
-
-pub fn monster(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position {
-            pt: Point::new(x, y),
-        })
-        .with(Renderable {
-            glyph: rltk::to_cp437('g'),
-            fg: RGB::named(rltk::RED),
-            bg: RGB::named(rltk::BLACK),
+        .with(Initiative {
+            base_speed: NORMAL_SPEED,
+            energy: 0,
         })
-        .with(Viewshed {
-            visible_tiles: Vec::new(),
-            range: 8,
-            dirty: true,
-        })
-        .with(Name {
-            name: "Goblin".to_string(),
-        })
-        .with(Monster {})
-        .with(CombatStats {
-            max_hp: 10,
-            cur_hp: 10,
-            atk: 4,
-            def: 1,
+        .with(attributes)
+        .with(Skills {
+            melee: 1,
+            defense: 1,
+            magic: 0,
         })
-        .with(BlocksTile {})
-        .with(BlocksSight {})
+        .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
-pub fn potion(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
-        .with(Position {
-            pt: Point::new(x, y),
-        })
-        .with(Renderable {
-            glyph: rltk::to_cp437('!'),
-            fg: RGB::named(rltk::MAGENTA),
-            bg: RGB::named(rltk::BLACK),
-        })
-        .with(Name {
-            name: "Health Potion".to_string(),
-        })
-        .with(Item {})
-        .with(Potion {})
-        .build();
+pub fn spawn_count(rng: &mut RandomNumberGenerator, depth: i32) -> i32 {
+    return max(0, rng.roll_dice(1, 7) + (depth - 1) - 3);
 }
 
-pub fn populate_room(ecs: &mut World, room: &Rect) {
-    let mut spawn_points_mob: Vec<Point> = Vec::new();
-    let mut spawn_points_item: Vec<Point> = Vec::new();
+/// Spawns a depth-appropriate number of entities on the given tiles, skipping
+/// walls and tiles that already hold something.
+pub fn populate_region(
+    ecs: &mut World,
+    region: &[usize],
+    depth: i32,
+    rng: &mut RandomNumberGenerator,
+) {
+    let spawn_table = ecs.fetch::<RawMaster>().spawn_table(depth);
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let occupied: Vec<Point> = positions.join().map(|pos| pos.pt).collect();
 
-    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-    let num_mob = rng.range(0, 5);
-    let num_item = rng.range(0, 2);
+    let mut candidates: Vec<usize> = region
+        .iter()
+        .filter(|idx| map.tiles[**idx] == TileType::Floor)
+        .filter(|idx| !occupied.contains(&map.index_to_point2d(**idx as i32)))
+        .cloned()
+        .collect();
 
-    for _i in 0..num_mob {
-        let mut ok = false;
-        while !ok {
-            let x = rng.range(0, room.x2 - room.x1);
-            let y = rng.range(0, room.y2 - room.y1);
-            let pt = Point::new(room.x1 + x, room.y1 + y);
-
-            if !spawn_points_mob.contains(&pt) {
-                spawn_points_mob.push(pt);
-                ok = true;
-            }
+    let mut spawn_points: Vec<(Point, String)> = Vec::new();
+    let num_spawns = min(spawn_count(rng, depth), candidates.len() as i32);
+    for _i in 0..num_spawns {
+        let idx = candidates.remove(rng.range(0, candidates.len()));
+        if let Some(name) = spawn_table.roll(rng) {
+            spawn_points.push((map.index_to_point2d(idx as i32), name));
         }
     }
+    std::mem::drop(positions);
+    std::mem::drop(map);
 
-    for _i in 0..num_item {
-        let mut ok = false;
-        while !ok {
-            let x = rng.range(0, room.x2 - room.x1);
-            let y = rng.range(0, room.y2 - room.y1);
-            let pt = Point::new(room.x1 + x, room.y1 + y);
-
-            if !spawn_points_item.contains(&pt) {
-                spawn_points_item.push(pt);
-                ok = true;
-            }
-        }
+    for (pt, name) in spawn_points.iter() {
+        spawn_named(ecs, name, *pt);
     }
-    std::mem::drop(rng);
+}
 
-    for pt in spawn_points_mob.iter() {
-        monster(ecs, pt.x, pt.y);
-    }
-    for pt in spawn_points_item.iter() {
-        potion(ecs, pt.x, pt.y);
+/// Spawns entities inside a rectangular room, treating its tiles as a region.
+pub fn populate_room(ecs: &mut World, room: &Rect, depth: i32, rng: &mut RandomNumberGenerator) {
+    let region = rect_tiles(&ecs.fetch::<Map>(), room);
+    populate_region(ecs, &region, depth, rng);
+}
+
+pub fn dump_spawn_table(raws: &RawMaster, depth: i32) {
+    let table = raws.spawn_table(depth);
+
+    println!(
+        "Spawn table for depth {} ({} to {} spawns per region, total weight {}):",
+        depth,
+        max(0, depth - 3),
+        max(0, depth + 3),
+        table.total_weight
+    );
+    for line in table.describe() {
+        println!("  {}", line);
     }
 }
@@ -1,16 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameLog {
     pub entries: Vec<String>,
+    pub max_entries: usize,
 }
 
 impl GameLog {
     pub fn new(size: usize) -> GameLog {
         GameLog {
             entries: Vec::with_capacity(size),
+            max_entries: size,
         }
     }
 
     pub fn add_message(&mut self, msg: String) {
-        if self.entries.len() == self.entries.capacity() {
+        if self.entries.len() >= self.max_entries {
             self.entries.pop();
         }
 
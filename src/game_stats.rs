@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct GameStats {
+    pub turns: i32,
+    pub kills: i32,
+    pub last_attacker: Option<String>,
+}
+
+impl GameStats {
+    pub fn new() -> GameStats {
+        return GameStats {
+            turns: 0,
+            kills: 0,
+            last_attacker: None,
+        };
+    }
+
+    pub fn cause_of_death(&self) -> String {
+        match &self.last_attacker {
+            Some(name) => return format!("Killed by a {}", name),
+            None => return "Died of unknown causes".to_string(),
+        }
+    }
+}
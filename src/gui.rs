@@ -1,9 +1,12 @@
+use super::attributes::*;
 use super::components::*;
-use super::{GameLog, Map};
+use super::{DungeonMaster, GameLog, GameStats, Map};
 
 // use std::iter::Iterator;
+use std::cmp::max;
 
-use rltk::{Algorithm2D, Console, Point, Rltk, RGB};
+use rltk::{Algorithm2D, Console, Point, Rltk, VirtualKeyCode, RGB};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
 pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
@@ -18,11 +21,13 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
 
     let player_entity = *ecs.fetch::<Entity>();
     let combat_stats = ecs.read_storage::<CombatStats>();
+    let attributes = ecs.read_storage::<Attributes>();
     let names = ecs.read_storage::<Name>();
 
     let stats = combat_stats.get(player_entity).unwrap();
+    let most_hp = max_hp(stats.base_hp, attributes.get(player_entity));
     let name = names.get(player_entity).unwrap();
-    let health = format!("{} | HP: {} / {} ", name.name, stats.cur_hp, stats.max_hp);
+    let health = format!("{} | HP: {} / {} ", name.name, stats.cur_hp, most_hp);
     ctx.print_color(
         1,
         43,
@@ -35,7 +40,7 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         43,
         51,
         stats.cur_hp,
-        stats.max_hp,
+        most_hp,
         RGB::named(rltk::RED),
         RGB::named(rltk::BLACK),
     );
@@ -56,9 +61,55 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         y += 1;
     }
 
+    let seed = ecs.fetch::<DungeonMaster>().seed;
+    ctx.print_color(
+        2,
+        49,
+        RGB::named(rltk::GREY),
+        RGB::named(rltk::BLACK),
+        &format!(" Seed: {} ", seed),
+    );
+
+    draw_equipment(ecs, ctx);
     draw_tooltips(ecs, ctx);
 }
 
+pub fn draw_equipment(ecs: &World, ctx: &mut Rltk) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let names = ecs.read_storage::<Name>();
+
+    let mut weapon = "-";
+    let mut armor = "-";
+    for (gear, name) in (&equipped, &names).join() {
+        if gear.owner == player_entity {
+            match gear.slot {
+                EquipmentSlot::Weapon => weapon = &name.name,
+                EquipmentSlot::Armor => armor = &name.name,
+            }
+        }
+    }
+
+    ctx.print_color(
+        58,
+        44,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Equipment",
+    );
+    ctx.print(58, 45, &format!("Weapon: {}", weapon));
+    ctx.print(58, 46, &format!("Armor:  {}", armor));
+
+    let depth = ecs.fetch::<Map>().depth;
+    ctx.print_color(
+        58,
+        48,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        &format!("Depth: {}", depth),
+    );
+}
+
 pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let map = &*ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
@@ -94,3 +145,475 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         }
     }
 }
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemMenuResult {
+    Cancel,
+    NoResponse,
+    Selected,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemAction {
+    Use,
+    Drop,
+    Inspect,
+}
+
+pub fn backpack_contents(ecs: &World, owner: Entity) -> Vec<Entity> {
+    let entities = ecs.entities();
+    let backpack = ecs.read_storage::<InBackpack>();
+
+    let mut contents: Vec<Entity> = Vec::new();
+    for (entity, pack) in (&entities, &backpack).join() {
+        if pack.owner == owner {
+            contents.push(entity);
+        }
+    }
+
+    return contents;
+}
+
+pub fn show_inventory(ecs: &World, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let contents = backpack_contents(ecs, player_entity);
+
+    let count = contents.len() as i32;
+    let mut y = 25 - (count / 2);
+    ctx.draw_box(
+        15,
+        y - 2,
+        31,
+        count + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Inventory",
+    );
+    ctx.print_color(
+        18,
+        y + count + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to cancel",
+    );
+
+    for (i, item) in contents.iter().enumerate() {
+        let letter = (97 + i as u8) as char;
+        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
+        ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), rltk::to_cp437(letter));
+        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+        ctx.print(21, y, &names.get(*item).unwrap().name);
+        if equipped.get(*item).is_some() {
+            ctx.print_color(
+                34,
+                y,
+                RGB::named(rltk::CYAN),
+                RGB::named(rltk::BLACK),
+                "(equipped)",
+            );
+        }
+        y += 1;
+    }
+
+    match ctx.key {
+        None => return (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => return (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count {
+                    return (ItemMenuResult::Selected, Some(contents[selection as usize]));
+                }
+                return (ItemMenuResult::NoResponse, None);
+            }
+        },
+    }
+}
+
+pub fn show_item_actions(
+    ecs: &World,
+    ctx: &mut Rltk,
+    item: Entity,
+) -> (ItemMenuResult, Option<ItemAction>) {
+    let names = ecs.read_storage::<Name>();
+    let name = &names.get(item).unwrap().name;
+
+    let width = max(31, name.len() as i32 + 6);
+    ctx.draw_box(
+        15,
+        21,
+        width,
+        6,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        21,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        name,
+    );
+    ctx.print(18, 23, "(u) Use");
+    ctx.print(18, 24, "(d) Drop");
+    ctx.print(18, 25, "(i) Inspect");
+    ctx.print_color(
+        18,
+        27,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to cancel",
+    );
+
+    match ctx.key {
+        None => return (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => return (ItemMenuResult::Cancel, None),
+            VirtualKeyCode::U => return (ItemMenuResult::Selected, Some(ItemAction::Use)),
+            VirtualKeyCode::D => return (ItemMenuResult::Selected, Some(ItemAction::Drop)),
+            VirtualKeyCode::I => return (ItemMenuResult::Selected, Some(ItemAction::Inspect)),
+            _ => return (ItemMenuResult::NoResponse, None),
+        },
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CharacterSheetResult {
+    Open,
+    Close,
+}
+
+pub fn show_character(ecs: &World, ctx: &mut Rltk) -> CharacterSheetResult {
+    let player_entity = *ecs.fetch::<Entity>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let initiatives = ecs.read_storage::<Initiative>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let skills = ecs.read_storage::<Skills>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let melee_bonus = ecs.read_storage::<MeleeBonus>();
+    let defense_bonus = ecs.read_storage::<DefenseBonus>();
+
+    let stats = combat_stats.get(player_entity).unwrap();
+    let attrs = attributes.get(player_entity);
+    let skill = skills.get(player_entity);
+    let base_speed = initiatives.get(player_entity).map_or(0, |i| i.base_speed);
+
+    let mut weapon = 0;
+    let mut armor = 0;
+    for (gear, bonus) in (&equipped, &melee_bonus).join() {
+        if gear.owner == player_entity {
+            weapon += bonus.atk;
+        }
+    }
+    for (gear, bonus) in (&equipped, &defense_bonus).join() {
+        if gear.owner == player_entity {
+            armor += bonus.def;
+        }
+    }
+
+    let attr_line =
+        |label: &str, value: i32| format!("{:<13}{:>3} ({:+})", label, value, attr_bonus(value));
+    let skill_value = |f: fn(&Skills) -> i32| skill.map_or(0, f);
+
+    let hp_bonus = HP_PER_FITNESS * fitness_bonus(attrs);
+    let speed_bonus = SPEED_PER_QUICKNESS * quickness_bonus(attrs);
+    let to_hit = stats.atk + melee_to_hit(attrs, skill) + weapon;
+    let damage_bonus = stats.damage.bonus + melee_damage(attrs);
+    let total_defense = BASE_DEFENSE + stats.def + defense(attrs, skill) + armor;
+    let carried = backpack_contents(ecs, player_entity).len();
+
+    let mut damage = format!("{}d{}", stats.damage.n_dice, stats.damage.die_type);
+    if damage_bonus != 0 {
+        damage = format!("{}{:+}", damage, damage_bonus);
+    }
+
+    let lines = vec![
+        "Attributes".to_string(),
+        attr_line("Might", attrs.map_or(10, |a| a.might)),
+        attr_line("Fitness", attrs.map_or(10, |a| a.fitness)),
+        attr_line("Quickness", attrs.map_or(10, |a| a.quickness)),
+        attr_line("Intelligence", attrs.map_or(10, |a| a.intelligence)),
+        String::new(),
+        "Skills".to_string(),
+        format!("{:<13}{:>3}", "Melee", skill_value(|s| s.melee)),
+        format!("{:<13}{:>3}", "Defense", skill_value(|s| s.defense)),
+        format!("{:<13}{:>3}", "Magic", skill_value(|s| s.magic)),
+        String::new(),
+        "Derived".to_string(),
+        format!(
+            "{:<9}{:>6} = {} base {:+} ({} x Fitness bonus)",
+            "Max HP",
+            max_hp(stats.base_hp, attrs),
+            stats.base_hp,
+            hp_bonus,
+            HP_PER_FITNESS
+        ),
+        format!(
+            "{:<9}{:>6} = d20 + {} atk {:+} Might {:+} Melee {:+} weapon",
+            "To-hit",
+            format!("{:+}", to_hit),
+            stats.atk,
+            might_bonus(attrs),
+            skill_value(|s| s.melee),
+            weapon
+        ),
+        format!(
+            "{:<9}{:>6} = {} {:+} Might",
+            "Damage",
+            damage,
+            format!("{}d{}{:+}", stats.damage.n_dice, stats.damage.die_type, stats.damage.bonus),
+            melee_damage(attrs)
+        ),
+        format!(
+            "{:<9}{:>6} = {} {:+} def {:+} Quickness {:+} Defense {:+} armor",
+            "Defense",
+            total_defense,
+            BASE_DEFENSE,
+            stats.def,
+            quickness_bonus(attrs),
+            skill_value(|s| s.defense),
+            armor
+        ),
+        format!(
+            "{:<9}{:>6} = {} base {:+} ({} x Quickness bonus)",
+            "Speed",
+            initiative_speed(base_speed, attrs),
+            base_speed,
+            speed_bonus,
+            SPEED_PER_QUICKNESS
+        ),
+        format!(
+            "{:<9}{:>6} = {} {:+} ({} x Might bonus), carrying {}",
+            "Carry",
+            carry_capacity(attrs),
+            BASE_CARRY,
+            CARRY_PER_MIGHT * might_bonus(attrs),
+            CARRY_PER_MIGHT,
+            carried
+        ),
+        format!(
+            "{:<9}{:>6} = {:+} Intelligence {:+} Magic, added to potions",
+            "Healing",
+            format!("{:+}", healing(attrs, skill)),
+            intelligence_bonus(attrs),
+            skill_value(|s| s.magic)
+        ),
+    ];
+
+    let height = lines.len() as i32;
+    let mut y = 25 - (height / 2);
+    ctx.draw_box(
+        3,
+        y - 2,
+        73,
+        height + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        6,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Character",
+    );
+    ctx.print_color(
+        6,
+        y + height + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to close",
+    );
+
+    for line in lines.iter() {
+        ctx.print(5, y, line);
+        y += 1;
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) | Some(VirtualKeyCode::C) => {
+            return CharacterSheetResult::Close
+        }
+        _ => return CharacterSheetResult::Open,
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum MainMenuSelection {
+    NewGame,
+    Continue,
+    Quit,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum MainMenuResult {
+    NoSelection { selected: MainMenuSelection },
+    Selected { selected: MainMenuSelection },
+}
+
+pub fn main_menu(
+    ctx: &mut Rltk,
+    selection: MainMenuSelection,
+    can_continue: bool,
+    mouse_moved: bool,
+    message: &Option<String>,
+) -> MainMenuResult {
+    let mut options = vec![(MainMenuSelection::NewGame, "New Game")];
+    if can_continue {
+        options.push((MainMenuSelection::Continue, "Continue"));
+    }
+    options.push((MainMenuSelection::Quit, "Quit"));
+
+    let mut current = options.iter().position(|(o, _)| *o == selection).unwrap_or(0);
+
+    ctx.print_color(
+        34,
+        15,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Rust Roguelike",
+    );
+
+    // Hovering only moves the selection when the mouse moves, so a cursor
+    // resting on the menu does not fight the keyboard.
+    let (m_x, m_y) = ctx.mouse_pos();
+    let mut hovered: Option<usize> = None;
+    for (i, (_option, label)) in options.iter().enumerate() {
+        let x = 40 - (label.len() as i32 / 2);
+        if m_y == 24 + i as i32 && m_x >= x && m_x < x + label.len() as i32 {
+            hovered = Some(i);
+        }
+    }
+    if let Some(i) = hovered {
+        if mouse_moved || ctx.left_click {
+            current = i;
+        }
+    }
+
+    let mut y = 24;
+    for (i, (_option, label)) in options.iter().enumerate() {
+        let fg = if i == current {
+            RGB::named(rltk::MAGENTA)
+        } else {
+            RGB::named(rltk::WHITE)
+        };
+        ctx.print_color(40 - (label.len() as i32 / 2), y, fg, RGB::named(rltk::BLACK), label);
+        y += 1;
+    }
+
+    if let Some(msg) = message {
+        ctx.print_color(
+            max(0, 40 - (msg.len() as i32 / 2)),
+            30,
+            RGB::named(rltk::RED),
+            RGB::named(rltk::BLACK),
+            msg,
+        );
+    }
+
+    let selected = options[current].0;
+    if ctx.left_click && hovered.is_some() {
+        return MainMenuResult::Selected { selected };
+    }
+
+    match ctx.key {
+        None => return MainMenuResult::NoSelection { selected },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => {
+                return MainMenuResult::NoSelection {
+                    selected: MainMenuSelection::Quit,
+                }
+            }
+            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 => {
+                let prev = (current + options.len() - 1) % options.len();
+                return MainMenuResult::NoSelection {
+                    selected: options[prev].0,
+                };
+            }
+            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 => {
+                let next = (current + 1) % options.len();
+                return MainMenuResult::NoSelection {
+                    selected: options[next].0,
+                };
+            }
+            VirtualKeyCode::Return => return MainMenuResult::Selected { selected },
+            _ => return MainMenuResult::NoSelection { selected },
+        },
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum GameOverResult {
+    NoSelection,
+    MainMenu,
+    Quit,
+}
+
+pub fn game_over(ecs: &World, ctx: &mut Rltk, message: &Option<String>) -> GameOverResult {
+    let game_stats = ecs.fetch::<GameStats>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let lines = [
+        game_stats.cause_of_death(),
+        format!("on depth {}.", depth),
+        String::new(),
+        format!("You survived {} turns.", game_stats.turns),
+        format!("You slew {} monsters.", game_stats.kills),
+    ];
+
+    ctx.print_color(
+        35,
+        15,
+        RGB::named(rltk::RED),
+        RGB::named(rltk::BLACK),
+        "You are dead!",
+    );
+
+    let mut y = 18;
+    for line in lines.iter() {
+        ctx.print_color(
+            max(0, 40 - (line.len() as i32 / 2)),
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            line,
+        );
+        y += 1;
+    }
+
+    let prompt = "Press Enter to return to the main menu, or Escape to quit.";
+    ctx.print_color(
+        40 - (prompt.len() as i32 / 2),
+        26,
+        RGB::named(rltk::MAGENTA),
+        RGB::named(rltk::BLACK),
+        prompt,
+    );
+
+    if let Some(msg) = message {
+        ctx.print_color(
+            max(0, 40 - (msg.len() as i32 / 2)),
+            30,
+            RGB::named(rltk::RED),
+            RGB::named(rltk::BLACK),
+            msg,
+        );
+    }
+
+    match ctx.key {
+        None => return GameOverResult::NoSelection,
+        Some(key) => match key {
+            VirtualKeyCode::Return => return GameOverResult::MainMenu,
+            VirtualKeyCode::Escape => return GameOverResult::Quit,
+            _ => return GameOverResult::NoSelection,
+        },
+    }
+}
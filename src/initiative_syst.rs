@@ -0,0 +1,176 @@
+use super::attributes::initiative_speed;
+use super::{Attributes, GameStats, Initiative, MyTurn, Position, RunState};
+use specs::prelude::*;
+
+pub const TURN_COST: i32 = 100;
+pub const NORMAL_SPEED: i32 = 100;
+
+/// Returns how many ticks pass before the first of the given actors has
+/// enough energy for a turn, or `None` when there is nobody to wait for. Each
+/// actor is an `(energy, speed)` pair; speeds below 1 count as 1.
+pub fn ticks_until_ready(actors: &[(i32, i32)]) -> Option<i32> {
+    return actors
+        .iter()
+        .map(|&(energy, speed)| {
+            let speed = std::cmp::max(1, speed);
+            let missing = std::cmp::max(0, TURN_COST - energy);
+            (missing + speed - 1) / speed
+        })
+        .min();
+}
+
+/// Returns an actor's energy after `ticks` ticks at the given speed.
+pub fn energy_after(energy: i32, speed: i32, ticks: i32) -> i32 {
+    return energy + std::cmp::max(1, speed) * ticks;
+}
+
+pub struct InitiativeSyst {}
+
+impl<'a> System<'a> for InitiativeSyst {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RunState>,
+        WriteExpect<'a, GameStats>,
+        WriteStorage<'a, Initiative>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Attributes>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player,
+            mut runstate,
+            mut game_stats,
+            mut initiatives,
+            mut turns,
+            positions,
+            attributes,
+        ) = data;
+
+        // Turns only last for the pass that granted them; PreRun passes must
+        // not let monsters act on markers left over from the last tick.
+        turns.clear();
+
+        if *runstate != RunState::Ticking {
+            return;
+        }
+
+        // Skip ahead to the moment the next actor is ready, so fast actors
+        // can take several turns between two of the player's.
+        let actors: Vec<(i32, i32)> = (&entities, &initiatives, &positions)
+            .join()
+            .map(|(entity, initiative, _pos)| {
+                let speed = initiative_speed(initiative.base_speed, attributes.get(entity));
+                (initiative.energy, speed)
+            })
+            .collect();
+        let wait = match ticks_until_ready(&actors) {
+            Some(wait) => wait,
+            None => {
+                *runstate = RunState::AwaitingInput;
+                return;
+            }
+        };
+
+        for (entity, initiative, _pos) in (&entities, &mut initiatives, &positions).join() {
+            let speed = initiative_speed(initiative.base_speed, attributes.get(entity));
+            initiative.energy = energy_after(initiative.energy, speed, wait);
+            if initiative.energy < TURN_COST {
+                continue;
+            }
+
+            initiative.energy -= TURN_COST;
+            turns
+                .insert(entity, MyTurn {})
+                .expect("Unable to insert turn.");
+
+            if entity == *player {
+                game_stats.turns += 1;
+                *runstate = RunState::AwaitingInput;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs one scheduling pass over `(energy, speed)` actors and returns the
+    /// indices of the actors that get a turn.
+    fn schedule(actors: &mut [(i32, i32)]) -> Vec<usize> {
+        let wait = ticks_until_ready(actors).unwrap();
+        let mut ready = Vec::new();
+        for (i, actor) in actors.iter_mut().enumerate() {
+            actor.0 = energy_after(actor.0, actor.1, wait);
+            if actor.0 >= TURN_COST {
+                actor.0 -= TURN_COST;
+                ready.push(i);
+            }
+        }
+        return ready;
+    }
+
+    #[test]
+    fn fast_actor_acts_several_times_before_slow_one() {
+        let mut actors = vec![(0, 300), (0, 50)];
+        let mut fast_turns = 0;
+        loop {
+            let ready = schedule(&mut actors);
+            if ready.contains(&1) {
+                break;
+            }
+            fast_turns += ready.len();
+        }
+        assert_eq!(fast_turns, 3);
+
+        let mut turns = [0; 2];
+        while turns[1] < 10 {
+            for i in schedule(&mut actors) {
+                turns[i] += 1;
+            }
+        }
+        assert_eq!(turns[0], 60);
+    }
+
+    #[test]
+    fn speed_is_floored_at_one() {
+        assert_eq!(ticks_until_ready(&[(0, 0)]), Some(TURN_COST));
+        assert_eq!(ticks_until_ready(&[(0, -50)]), Some(TURN_COST));
+        assert_eq!(energy_after(0, 0, 10), 10);
+        assert_eq!(energy_after(0, -50, 10), 10);
+    }
+
+    #[test]
+    fn ready_actor_needs_no_wait() {
+        assert_eq!(ticks_until_ready(&[(TURN_COST, 100), (0, 100)]), Some(0));
+        assert_eq!(ticks_until_ready(&[(TURN_COST + 50, 1)]), Some(0));
+    }
+
+    #[test]
+    fn no_positioned_actors_hands_control_back() {
+        assert_eq!(ticks_until_ready(&[]), None);
+
+        let mut ecs = World::new();
+        ecs.register::<Initiative>();
+        ecs.register::<MyTurn>();
+        ecs.register::<Position>();
+        ecs.register::<Attributes>();
+        let player = ecs
+            .create_entity()
+            .with(Initiative {
+                base_speed: NORMAL_SPEED,
+                energy: 0,
+            })
+            .build();
+        ecs.insert(player);
+        ecs.insert(GameStats::new());
+        ecs.insert(RunState::Ticking);
+
+        InitiativeSyst {}.run_now(&ecs);
+        assert!(*ecs.fetch::<RunState>() == RunState::AwaitingInput);
+    }
+}
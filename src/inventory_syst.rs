@@ -1,6 +1,8 @@
+use super::attributes::{healing, max_hp};
 use super::components::*;
 use super::game_log::*;
 use specs::prelude::*;
+use std::cmp::{max, min};
 
 pub struct ItemManageSyst {}
 
@@ -29,3 +31,167 @@ impl<'a> System<'a> for ItemManageSyst {
         pickup_msg.clear();
     }
 }
+
+pub struct ItemUseSyst {}
+
+impl<'a> System<'a> for ItemUseSyst {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, UseItemMessage>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Potion>,
+        ReadStorage<'a, Equippable>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, CombatStats>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player,
+            mut log,
+            entities,
+            mut use_msg,
+            names,
+            potions,
+            equippables,
+            mut equipped,
+            mut combat_stats,
+            attributes,
+            skills,
+        ) = data;
+
+        for (entity, use_item, stats) in (&entities, &use_msg, &mut combat_stats).join() {
+            let item_name = &names.get(use_item.item).unwrap().name;
+
+            if let Some(equippable) = equippables.get(use_item.item) {
+                if equipped.get(use_item.item).is_some() {
+                    equipped.remove(use_item.item);
+                    if entity == *player {
+                        log.add_message(format!("You unequip the {}.", item_name));
+                    }
+                    continue;
+                }
+
+                let mut unequip_vec: Vec<Entity> = Vec::new();
+                for (other, gear) in (&entities, &equipped).join() {
+                    if gear.owner == entity && gear.slot == equippable.slot {
+                        unequip_vec.push(other);
+                    }
+                }
+                for other in unequip_vec {
+                    equipped.remove(other);
+                    if entity == *player {
+                        log.add_message(format!(
+                            "You unequip the {}.",
+                            names.get(other).unwrap().name
+                        ));
+                    }
+                }
+
+                equipped
+                    .insert(
+                        use_item.item,
+                        Equipped {
+                            owner: entity,
+                            slot: equippable.slot,
+                        },
+                    )
+                    .expect("Unable to equip item.");
+                if entity == *player {
+                    log.add_message(format!("You equip the {}.", item_name));
+                }
+            } else if let Some(potion) = potions.get(use_item.item) {
+                let prev_hp = stats.cur_hp;
+                let heal_amount = max(
+                    1,
+                    potion.heal_amount + healing(attributes.get(entity), skills.get(entity)),
+                );
+                let most_hp = max_hp(stats.base_hp, attributes.get(entity));
+                stats.cur_hp = min(most_hp, stats.cur_hp + heal_amount);
+
+                if entity == *player {
+                    log.add_message(format!(
+                        "You drink the {}, healing {} hp.",
+                        item_name,
+                        stats.cur_hp - prev_hp
+                    ));
+                }
+
+                entities
+                    .delete(use_item.item)
+                    .expect("Unable to delete used item.");
+            } else if entity == *player {
+                log.add_message(format!("You can't use the {}.", item_name));
+            }
+        }
+
+        use_msg.clear();
+    }
+}
+
+pub struct ItemDropSyst {}
+
+impl<'a> System<'a> for ItemDropSyst {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, DropItemMessage>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Equipped>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player,
+            mut log,
+            entities,
+            mut drop_msg,
+            names,
+            mut positions,
+            mut backpack,
+            mut equipped,
+        ) = data;
+
+        for (entity, drop) in (&entities, &drop_msg).join() {
+            let item_name = &names.get(drop.item).unwrap().name;
+            let drop_pt = match positions.get(entity) {
+                Some(pos) => pos.pt,
+                None => {
+                    if entity == *player {
+                        log.add_message(format!("You have nowhere to drop the {}.", item_name));
+                    } else if let Some(name) = names.get(entity) {
+                        log.add_message(format!(
+                            "{} has nowhere to drop the {}.",
+                            name.name, item_name
+                        ));
+                    }
+                    continue;
+                }
+            };
+            positions
+                .insert(drop.item, Position { pt: drop_pt })
+                .expect("Unable to insert dropped item position.");
+            backpack.remove(drop.item);
+            equipped.remove(drop.item);
+
+            if entity == *player {
+                log.add_message(format!("You drop the {}.", item_name));
+            } else {
+                log.add_message(format!(
+                    "{} drops the {}.",
+                    names.get(entity).unwrap().name,
+                    item_name
+                ));
+            }
+        }
+
+        drop_msg.clear();
+    }
+}
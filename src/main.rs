@@ -1,5 +1,9 @@
-use rltk::{Algorithm2D, Console, GameState, Point, Rltk};
+use rltk::{Algorithm2D, Console, GameState, Point, RandomNumberGenerator, Rltk};
+use serde::{Deserialize, Serialize};
+use specs::error::NoError;
 use specs::prelude::*;
+use specs::saveload::{ConvertSaveload, Marker};
+use std::sync::Arc;
 #[macro_use]
 extern crate specs_derive;
 
@@ -18,6 +22,9 @@ pub use visibility_syst::*;
 mod monster_syst;
 pub use monster_syst::*;
 
+mod initiative_syst;
+pub use initiative_syst::*;
+
 mod mapindex_syst;
 pub use mapindex_syst::*;
 
@@ -33,6 +40,9 @@ pub use gui::*;
 mod game_log;
 pub use game_log::*;
 
+mod game_stats;
+pub use game_stats::*;
+
 mod entity_spawn;
 pub use entity_spawn::*;
 
@@ -42,21 +52,56 @@ pub use inventory_syst::*;
 mod util;
 pub use util::*;
 
+mod raws;
+pub use raws::*;
+
+mod random_table;
+pub use random_table::*;
+
+mod dice;
+pub use dice::*;
+
+mod attributes;
+pub use attributes::*;
+
+mod dungeon;
+pub use dungeon::*;
+
+mod mapgen_debug;
+pub use mapgen_debug::*;
+
+mod save_load;
+
+mod map_builders;
+use map_builders::{MapBuilder, PrefabLibrary};
+
 const MAP_WIDTH: i32 = 80;
 const MAP_HEIGHT: i32 = 43;
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, ConvertSaveload)]
 pub enum RunState {
+    MainMenu { selection: MainMenuSelection },
     AwaitingInput,
     PreRun,
-    PlayerTurn,
-    MonsterTurn,
+    Ticking,
     ShowInventory,
+    ShowItemActions { item: Entity },
+    ShowCharacter,
+    NextLevel,
+    PreviousLevel,
+    MapGeneration,
+    GameOver,
     Exit,
 }
 
 pub struct State {
     pub ecs: World,
+    pub menu_message: Option<String>,
+    pub mouse_pos: (i32, i32),
+    pub seed: Option<u64>,
+    pub map_builder: Option<String>,
+    pub mapgen_debug: bool,
+    pub mapgen: MapGenPlayback,
 }
 
 impl State {
@@ -65,10 +110,16 @@ impl State {
         map.run_now(&self.ecs);
         let mut vis = VisibilitySystem {};
         vis.run_now(&self.ecs);
+        let mut initiative = InitiativeSyst {};
+        initiative.run_now(&self.ecs);
         let mut mon = MonsterAI {};
         mon.run_now(&self.ecs);
         let mut items = ItemManageSyst {};
         items.run_now(&self.ecs);
+        let mut use_items = ItemUseSyst {};
+        use_items.run_now(&self.ecs);
+        let mut drop_items = ItemDropSyst {};
+        drop_items.run_now(&self.ecs);
         let mut combat = CombatSyst {};
         combat.run_now(&self.ecs);
         let mut damage = DamageSyst {};
@@ -80,6 +131,193 @@ impl State {
         let mut map = MapIndexingSystem {};
         map.run_now(&self.ecs);
     }
+
+    fn build_level(&mut self, depth: i32) {
+        let mut rng = self.ecs.fetch::<DungeonMaster>().level_rng(depth);
+        let prefabs = Arc::clone(&self.ecs.fetch::<Arc<PrefabLibrary>>());
+        let inner = match &self.map_builder {
+            Some(name) => {
+                map_builders::builder_by_name(name, MAP_WIDTH, MAP_HEIGHT, depth, &prefabs)
+                    .expect("Unknown map builder.")
+            }
+            None => {
+                map_builders::random_builder(MAP_WIDTH, MAP_HEIGHT, depth, &prefabs, &mut rng)
+            }
+        };
+        let mut builder = map_builders::DoorBuilder::new(Box::new(
+            map_builders::PrefabBuilder::new(inner, prefabs),
+        ));
+        builder.build_map(&mut rng);
+
+        let mut map = builder.get_map();
+        let start = builder.get_starting_position();
+        if depth > 1 {
+            let idx = map.xy_idx(start.x, start.y);
+            map.tiles[idx] = TileType::UpStairs;
+        }
+        if self.mapgen_debug {
+            let mut history = builder.get_snapshot_history();
+            history.push(map.tiles.clone());
+            self.mapgen.reset(history);
+        }
+        self.ecs.insert(map);
+
+        for (pt, name) in builder.get_spawn_list().iter() {
+            spawn_named(&mut self.ecs, name, *pt);
+        }
+        for room in builder.get_spawn_rooms().iter() {
+            entity_spawn::populate_room(&mut self.ecs, room, depth, &mut rng);
+        }
+        for region in builder.get_spawn_regions(&mut rng).iter() {
+            entity_spawn::populate_region(&mut self.ecs, region, depth, &mut rng);
+        }
+
+        self.place_player(start);
+    }
+
+    fn level_start_state(&self) -> RunState {
+        if self.mapgen_debug && !self.mapgen.is_empty() {
+            return RunState::MapGeneration;
+        }
+        return RunState::PreRun;
+    }
+
+    fn place_player(&mut self, pt: Point) {
+        self.ecs.insert(pt);
+
+        let player_entity = *self.ecs.fetch::<Entity>();
+        let mut positions = self.ecs.write_storage::<Position>();
+        if let Some(pos) = positions.get_mut(player_entity) {
+            pos.pt = pt;
+        }
+
+        let mut viewsheds = self.ecs.write_storage::<Viewshed>();
+        for vs in (&mut viewsheds).join() {
+            vs.visible_tiles.clear();
+            vs.dirty = true;
+        }
+    }
+
+    fn change_level(&mut self, offset: i32) {
+        dungeon::freeze_level_entities(&mut self.ecs);
+        self.mapgen.reset(Vec::new());
+
+        let depth = self.ecs.fetch::<Map>().depth + offset;
+        let stored = {
+            let map = self.ecs.fetch::<Map>();
+            let mut dungeon_master = self.ecs.fetch_mut::<DungeonMaster>();
+            dungeon_master.store_map(&map);
+            dungeon_master.get_map(depth)
+        };
+
+        match stored {
+            None => self.build_level(depth),
+            Some(map) => {
+                let arrival = if offset > 0 {
+                    TileType::UpStairs
+                } else {
+                    TileType::DownStairs
+                };
+                let pt = map
+                    .find_tile(arrival)
+                    .or_else(|| map.find_tile(TileType::Floor))
+                    .expect("Stored level has no open floor.");
+                self.ecs.insert(map);
+                self.place_player(pt);
+            }
+        }
+
+        dungeon::thaw_level_entities(&mut self.ecs);
+
+        let mut log = self.ecs.fetch_mut::<GameLog>();
+        if offset > 0 {
+            log.add_message(format!("You descend to depth {}.", depth));
+        } else {
+            log.add_message(format!("You climb back up to depth {}.", depth));
+        }
+    }
+
+    fn restore_world(&mut self, mut ecs: World) {
+        let raws = self
+            .ecs
+            .remove::<RawMaster>()
+            .expect("Raws were not loaded.");
+        let prefabs = self
+            .ecs
+            .remove::<Arc<PrefabLibrary>>()
+            .expect("Prefabs were not loaded.");
+        let rng = self
+            .ecs
+            .remove::<RandomNumberGenerator>()
+            .expect("Random number generator was not created.");
+        ecs.insert(raws);
+        ecs.insert(prefabs);
+        ecs.insert(rng);
+
+        self.ecs = ecs;
+    }
+
+    fn reset_world(&mut self) {
+        let raws = self
+            .ecs
+            .remove::<RawMaster>()
+            .expect("Raws were not loaded.");
+        let prefabs = self
+            .ecs
+            .remove::<Arc<PrefabLibrary>>()
+            .expect("Prefabs were not loaded.");
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => dungeon::random_seed(),
+        };
+
+        let mut ecs = World::new();
+        register_components(&mut ecs);
+        ecs.insert(RunState::MainMenu {
+            selection: main_menu_default(),
+        });
+        ecs.insert(GameLog::new(10));
+        ecs.insert(GameStats::new());
+        ecs.insert(RandomNumberGenerator::seeded(seed));
+        ecs.insert(raws);
+        ecs.insert(prefabs);
+        ecs.insert(DungeonMaster::new(seed));
+        self.ecs = ecs;
+        self.mapgen.reset(Vec::new());
+    }
+
+    fn new_game(&mut self) {
+        self.reset_world();
+
+        // Also print the seed outside the window so it survives a crash and
+        // can be pasted into bug reports.
+        let seed = self.ecs.fetch::<DungeonMaster>().seed;
+        eprintln!("Dungeon seed: {}", seed);
+        self.ecs
+            .fetch_mut::<GameLog>()
+            .add_message(format!("Dungeon seed: {}", seed));
+
+        let player_entity = entity_spawn::player(&mut self.ecs, 0, 0);
+        self.ecs.insert(player_entity);
+        self.build_level(1);
+    }
+
+    fn draw_world(&self, ctx: &mut Rltk) {
+        let map = self.ecs.fetch::<Map>();
+        draw_map(&map, ctx);
+
+        let positions = self.ecs.read_storage::<Position>();
+        let renderables = self.ecs.read_storage::<Renderable>();
+
+        for (pos, render) in (&positions, &renderables).join() {
+            let idx = map.point2d_to_index(pos.pt) as usize;
+            if map.visible_tiles[idx] {
+                ctx.set(pos.pt.x, pos.pt.y, render.fg, render.bg, render.glyph);
+            }
+        }
+
+        draw_ui(&self.ecs, ctx);
+    }
 }
 
 impl GameState for State {
@@ -88,6 +326,40 @@ impl GameState for State {
 
         let mut new_runstate = *self.ecs.fetch::<RunState>();
         match new_runstate {
+            RunState::MainMenu { .. } | RunState::GameOver => {}
+            RunState::MapGeneration => self.mapgen.draw(&self.ecs.fetch::<Map>(), ctx),
+            _ => self.draw_world(ctx),
+        }
+
+        match new_runstate {
+            RunState::MainMenu { selection } => {
+                let mouse_moved = ctx.mouse_pos() != self.mouse_pos;
+                self.mouse_pos = ctx.mouse_pos();
+                let can_continue = save_load::save_exists();
+                match main_menu(ctx, selection, can_continue, mouse_moved, &self.menu_message) {
+                    MainMenuResult::NoSelection { selected } => {
+                        new_runstate = RunState::MainMenu {
+                            selection: selected,
+                        };
+                    }
+                    MainMenuResult::Selected { selected } => match selected {
+                        MainMenuSelection::NewGame => {
+                            self.new_game();
+                            self.menu_message = None;
+                            new_runstate = self.level_start_state();
+                        }
+                        MainMenuSelection::Continue => match save_load::load_game() {
+                            Ok(ecs) => {
+                                self.restore_world(ecs);
+                                self.menu_message = None;
+                                new_runstate = *self.ecs.fetch::<RunState>();
+                            }
+                            Err(e) => self.menu_message = Some(e),
+                        },
+                        MainMenuSelection::Quit => ctx.quit(),
+                    },
+                }
+            }
             RunState::PreRun => {
                 self.run_systems();
                 new_runstate = RunState::AwaitingInput;
@@ -96,20 +368,77 @@ impl GameState for State {
                 self.update_map();
                 new_runstate = player_input(self, ctx);
             }
-            RunState::PlayerTurn => {
-                self.run_systems();
-                new_runstate = RunState::MonsterTurn;
-            }
-            RunState::MonsterTurn => {
-                self.run_systems();
-                new_runstate = RunState::AwaitingInput;
+            RunState::Ticking => {
+                while new_runstate == RunState::Ticking {
+                    self.run_systems();
+                    DamageSyst::delete_the_dead(&mut self.ecs);
+                    new_runstate = *self.ecs.fetch::<RunState>();
+                }
+                if new_runstate == RunState::GameOver {
+                    if let Err(e) = save_load::delete_save() {
+                        self.menu_message = Some(e);
+                    }
+                }
             }
             RunState::ShowInventory => {
-                new_runstate = RunState::AwaitingInput;
+                let (result, item) = show_inventory(&self.ecs, ctx);
+                match result {
+                    ItemMenuResult::Cancel => new_runstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        new_runstate = RunState::ShowItemActions {
+                            item: item.unwrap(),
+                        };
+                    }
+                }
+            }
+            RunState::ShowItemActions { item } => {
+                let (result, action) = show_item_actions(&self.ecs, ctx, item);
+                match result {
+                    ItemMenuResult::Cancel => new_runstate = RunState::ShowInventory,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        new_runstate = match action.unwrap() {
+                            ItemAction::Use => use_item(&mut self.ecs, item),
+                            ItemAction::Drop => drop_item(&mut self.ecs, item),
+                            ItemAction::Inspect => inspect_item(&mut self.ecs, item),
+                        };
+                    }
+                }
             }
-            RunState::Exit => {
-                ctx.quit();
+            RunState::ShowCharacter => match show_character(&self.ecs, ctx) {
+                CharacterSheetResult::Open => {}
+                CharacterSheetResult::Close => new_runstate = RunState::AwaitingInput,
+            },
+            RunState::NextLevel => {
+                self.change_level(1);
+                new_runstate = self.level_start_state();
             }
+            RunState::PreviousLevel => {
+                self.change_level(-1);
+                new_runstate = self.level_start_state();
+            }
+            RunState::MapGeneration => match self.mapgen.update(ctx) {
+                MapGenResult::Playing => {}
+                MapGenResult::Finished => new_runstate = RunState::PreRun,
+            },
+            RunState::GameOver => match game_over(&self.ecs, ctx, &self.menu_message) {
+                GameOverResult::NoSelection => {}
+                GameOverResult::MainMenu => {
+                    self.reset_world();
+                    self.menu_message = None;
+                    new_runstate = *self.ecs.fetch::<RunState>();
+                }
+                GameOverResult::Quit => ctx.quit(),
+            },
+            RunState::Exit => match save_load::save_game(&mut self.ecs, RunState::AwaitingInput) {
+                Ok(()) => ctx.quit(),
+                Err(e) => {
+                    let mut log = self.ecs.fetch_mut::<GameLog>();
+                    log.add_message(e);
+                    new_runstate = RunState::AwaitingInput;
+                }
+            },
         }
 
         let mut runstate_write = self.ecs.write_resource::<RunState>();
@@ -120,58 +449,104 @@ impl GameState for State {
             DamageSyst::delete_the_dead(&mut self.ecs);
         }
 
-        let map = self.ecs.fetch::<Map>();
-        draw_map(&map, ctx);
+    }
+}
 
-        let positions = self.ecs.read_storage::<Position>();
-        let renderables = self.ecs.read_storage::<Renderable>();
+fn main_menu_default() -> MainMenuSelection {
+    if save_load::save_exists() {
+        return MainMenuSelection::Continue;
+    }
+    return MainMenuSelection::NewGame;
+}
 
-        for (pos, render) in (&positions, &renderables).join() {
-            let idx = map.point2d_to_index(pos.pt) as usize;
-            if map.visible_tiles[idx] {
-                ctx.set(pos.pt.x, pos.pt.y, render.fg, render.bg, render.glyph);
-            }
+fn parse_seed(args: &[String]) -> Result<Option<u64>, String> {
+    let mut value: Option<String> = std::env::var("RL_SEED").ok();
+    if let Some(pos) = args.iter().position(|a| a == "--seed") {
+        match args.get(pos + 1) {
+            None => return Err("--seed requires a number".to_string()),
+            Some(arg) => value = Some(arg.clone()),
         }
+    }
 
-        draw_ui(&self.ecs, ctx);
+    match value {
+        None => return Ok(None),
+        Some(v) => match v.trim().parse::<u64>() {
+            Ok(seed) => return Ok(Some(seed)),
+            Err(_) => return Err(format!("Invalid seed '{}': expected a number", v)),
+        },
     }
 }
 
 fn main() {
+    let raws = match RawMaster::load("resources/spawns.json") {
+        Ok(raws) => raws,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let prefabs = match PrefabLibrary::load("resources/prefabs.json") {
+        Ok(prefabs) => prefabs,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = map_builders::check_raws(&raws, &prefabs) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 && args[1] == "--spawn-table" {
+        let depth = args.get(2).and_then(|d| d.parse::<i32>().ok()).unwrap_or(1);
+        dump_spawn_table(&raws, depth);
+        return;
+    }
+
+    let seed = match parse_seed(&args) {
+        Ok(seed) => seed,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let map_builder = match args.iter().position(|a| a == "--builder") {
+        None => None,
+        Some(pos) => match args.get(pos + 1) {
+            Some(name) if map_builders::builder_names().contains(&name.as_str()) => {
+                Some(name.clone())
+            }
+            _ => {
+                eprintln!(
+                    "--builder requires one of: {}",
+                    map_builders::builder_names().join(", ")
+                );
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let mapgen_debug = args.iter().any(|a| a == "--mapgen-debug");
+
     let context = Rltk::init_simple8x8(80, 50, "RL", "resources");
     // context.with_post_scanlines(true);
 
-    let mut gs = State { ecs: World::new() };
-    gs.ecs.insert(RunState::PreRun);
-    gs.ecs.insert(GameLog::new(10));
-    gs.ecs.insert(rltk::RandomNumberGenerator::new());
-
-    gs.ecs.register::<Position>();
-    gs.ecs.register::<Renderable>();
-    gs.ecs.register::<Name>();
-    gs.ecs.register::<Player>();
-    gs.ecs.register::<Monster>();
-    gs.ecs.register::<CombatStats>();
-    gs.ecs.register::<MeleeMessage>();
-    gs.ecs.register::<DamageMessage>();
-    gs.ecs.register::<Viewshed>();
-    gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<BlocksSight>();
-    gs.ecs.register::<Item>();
-    gs.ecs.register::<Potion>();
-    gs.ecs.register::<ItemPickupMessage>();
-    gs.ecs.register::<InBackpack>();
-
-    let map = Map::new_map_rooms(MAP_WIDTH, MAP_HEIGHT);
-    let (player_x, player_y) = map.rooms[0].center();
-    for room in map.rooms.iter().skip(1) {
-        entity_spawn::populate_room(&mut gs.ecs, room);
-    }
-
-    let player_entity = entity_spawn::player(&mut gs.ecs, player_x, player_y);
-
-    gs.ecs.insert(map);
-    gs.ecs.insert(player_entity);
-    gs.ecs.insert(Point::new(player_x, player_y));
+    let mut gs = State {
+        ecs: World::new(),
+        menu_message: None,
+        mouse_pos: (0, 0),
+        seed,
+        map_builder,
+        mapgen_debug,
+        mapgen: MapGenPlayback::new(),
+    };
+    gs.ecs.insert(raws);
+    gs.ecs.insert(Arc::new(prefabs));
+    gs.reset_world();
+
     rltk::main_loop(context, gs);
 }
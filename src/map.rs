@@ -1,16 +1,19 @@
 use std::cmp::{max, min};
 
 use super::util::*;
-use rltk::{Algorithm2D, BaseMap, Console, Point, RandomNumberGenerator, Rltk, RGB};
+use rltk::{Algorithm2D, BaseMap, Console, Point, Rltk, RGB};
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
+    DownStairs,
+    UpStairs,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Map {
     pub tiles: Vec<TileType>,
     pub rooms: Vec<Rect>,
@@ -20,7 +23,11 @@ pub struct Map {
     pub revealed_tiles: Vec<bool>,
     pub visible_tiles: Vec<bool>,
     pub blocked_tiles: Vec<bool>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub opaque_tiles: Vec<bool>,
+    #[serde(skip_serializing, skip_deserializing)]
     pub tile_content: Vec<Vec<Entity>>,
+    pub depth: i32,
 }
 
 impl Map {
@@ -28,7 +35,7 @@ impl Map {
         return (y * self.width + x) as usize;
     }
 
-    pub fn new(width: i32, height: i32) -> Map {
+    pub fn new(width: i32, height: i32, depth: i32) -> Map {
         let num = (width * height) as usize;
         return Map {
             tiles: vec![TileType::Wall; num],
@@ -39,98 +46,12 @@ impl Map {
             revealed_tiles: vec![false; num],
             visible_tiles: vec![false; num],
             blocked_tiles: vec![false; num],
+            opaque_tiles: vec![true; num],
             tile_content: vec![Vec::new(); num],
+            depth: depth,
         };
     }
 
-    pub fn new_map_rand() -> Map {
-        let mut map = Map {
-            tiles: vec![TileType::Floor; 80 * 50],
-            rooms: Vec::new(),
-            width: 80,
-            height: 50,
-            num_tiles: 80 * 50,
-            revealed_tiles: vec![false; 80 * 50],
-            visible_tiles: vec![false; 80 * 50],
-            blocked_tiles: vec![false; 80 * 50],
-            tile_content: vec![Vec::new(); 80 * 50],
-        };
-
-        // let mut map = Map::new(80, 50);
-
-        map.rooms
-            .push(Rect::new(1, 1, map.width - 1, map.height - 1));
-
-        for x in 0..80 {
-            let idx = map.xy_idx(x, 0);
-            map.tiles[idx] = TileType::Wall;
-            let idx = map.xy_idx(x, map.height - 1);
-            map.tiles[idx] = TileType::Wall;
-        }
-
-        for y in 0..50 {
-            let idx = map.xy_idx(0, y);
-            map.tiles[idx] = TileType::Wall;
-            let idx = map.xy_idx(map.width - 1, y);
-            map.tiles[idx] = TileType::Wall;
-        }
-
-        let mut rng = rltk::RandomNumberGenerator::new();
-        for _i in 0..400 {
-            let x = rng.roll_dice(1, 79);
-            let y = rng.roll_dice(1, 49);
-            let idx = map.xy_idx(x, y);
-
-            if idx != map.xy_idx(40, 25) {
-                map.tiles[idx] = TileType::Wall;
-            }
-        }
-
-        return map;
-    }
-
-    pub fn new_map_rooms(w: i32, h: i32) -> Map {
-        let mut map = Map::new(w, h);
-
-        const MAX_ROOMS: i32 = 32;
-        const MIN_SIZE: i32 = 5;
-        const MAX_SIZE: i32 = 12;
-        let mut rng = RandomNumberGenerator::new();
-
-        for _i in 0..MAX_ROOMS {
-            let w = rng.range(MIN_SIZE, MAX_SIZE);
-            let h = rng.range(MIN_SIZE, MAX_SIZE);
-            let x = rng.range(1, map.width - w);
-            let y = rng.range(1, map.height - h);
-            let new_room = Rect::new(x, y, w, h);
-            let mut ok = true;
-            for other_room in map.rooms.iter() {
-                if new_room.intersect(other_room) {
-                    ok = false;
-                }
-            }
-            if ok {
-                map.apply_room(&new_room);
-
-                if !map.rooms.is_empty() {
-                    let (new_x, new_y) = new_room.center();
-                    let (prev_x, prev_y) = map.rooms[map.rooms.len() - 1].center();
-                    if rng.roll_dice(1, 2) == 1 {
-                        map.apply_tunnel_h(prev_x, new_x, prev_y);
-                        map.apply_tunnel_v(prev_y, new_y, new_x);
-                    } else {
-                        map.apply_tunnel_v(prev_y, new_y, prev_x);
-                        map.apply_tunnel_h(prev_x, new_x, new_y);
-                    }
-                }
-
-                map.rooms.push(new_room);
-            }
-        }
-
-        return map;
-    }
-
     pub fn apply_room(&mut self, room: &Rect) {
         for y in room.y1..room.y2 {
             for x in room.x1..room.x2 {
@@ -177,6 +98,24 @@ impl Map {
         }
     }
 
+    pub fn populate_opaque(&mut self) {
+        self.opaque_tiles = self
+            .tiles
+            .iter()
+            .map(|tile| *tile == TileType::Wall)
+            .collect();
+    }
+
+    pub fn find_tile(&self, tile_type: TileType) -> Option<Point> {
+        for (idx, tile) in self.tiles.iter().enumerate() {
+            if *tile == tile_type {
+                return Some(self.index_to_point2d(idx as i32));
+            }
+        }
+
+        return None;
+    }
+
     pub fn clear_content_index(&mut self) {
         for content in self.tile_content.iter_mut() {
             content.clear();
@@ -187,7 +126,7 @@ impl Map {
 impl BaseMap for Map {
     fn is_opaque(&self, idx: i32) -> bool {
         if -1 < idx && idx < self.num_tiles {
-            return self.tiles[idx as usize] == TileType::Wall;
+            return self.opaque_tiles[idx as usize];
         } else {
             return false;
         }
@@ -267,6 +206,16 @@ pub fn draw_map(map: &Map, ctx: &mut Rltk) {
                     bg = RGB::from_f32(0.0, 0.0, 0.0);
                     glyph = rltk::to_cp437('#');
                 }
+                TileType::DownStairs => {
+                    fg = RGB::from_f32(0.0, 1.0, 1.0);
+                    bg = RGB::from_f32(0.0, 0.0, 0.0);
+                    glyph = rltk::to_cp437('>');
+                }
+                TileType::UpStairs => {
+                    fg = RGB::from_f32(0.0, 1.0, 1.0);
+                    bg = RGB::from_f32(0.0, 0.0, 0.0);
+                    glyph = rltk::to_cp437('<');
+                }
             }
 
             if !map.visible_tiles[idx] {
@@ -0,0 +1,133 @@
+use super::{Map, MapBuilder, Rect, TileType};
+use rltk::{Point, RandomNumberGenerator};
+
+const MIN_LEAF: i32 = 8;
+const MAX_LEAF: i32 = 20;
+const MIN_ROOM: i32 = 4;
+
+pub struct BspBuilder {
+    map: Map,
+    starting_position: Point,
+    history: Vec<Vec<TileType>>,
+}
+
+impl BspBuilder {
+    pub fn new(width: i32, height: i32, depth: i32) -> BspBuilder {
+        return BspBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(0, 0),
+            history: Vec::new(),
+        };
+    }
+
+    fn partition(&mut self, area: Rect, rng: &mut RandomNumberGenerator) -> Rect {
+        let width = area.x2 - area.x1;
+        let height = area.y2 - area.y1;
+        let can_split_v = width >= MIN_LEAF * 2;
+        let can_split_h = height >= MIN_LEAF * 2;
+
+        let small_enough = width <= MAX_LEAF && height <= MAX_LEAF;
+        if (!can_split_v && !can_split_h) || (small_enough && rng.roll_dice(1, 4) == 1) {
+            return self.carve_room(&area, rng);
+        }
+
+        let split_v = if !can_split_h {
+            true
+        } else if !can_split_v {
+            false
+        } else if width * 4 > height * 5 {
+            true
+        } else if height * 4 > width * 5 {
+            false
+        } else {
+            rng.roll_dice(1, 2) == 1
+        };
+
+        let (first, second) = if split_v {
+            let x = rng.range(area.x1 + MIN_LEAF, area.x2 - MIN_LEAF + 1);
+            (
+                Rect { x2: x, ..area.clone() },
+                Rect { x1: x, ..area.clone() },
+            )
+        } else {
+            let y = rng.range(area.y1 + MIN_LEAF, area.y2 - MIN_LEAF + 1);
+            (
+                Rect { y2: y, ..area.clone() },
+                Rect { y1: y, ..area.clone() },
+            )
+        };
+
+        let first_room = self.partition(first, rng);
+        let second_room = self.partition(second, rng);
+        self.connect(&first_room, &second_room, rng);
+
+        if rng.roll_dice(1, 2) == 1 {
+            return first_room;
+        }
+        return second_room;
+    }
+
+    fn carve_room(&mut self, leaf: &Rect, rng: &mut RandomNumberGenerator) -> Rect {
+        let max_w = leaf.x2 - leaf.x1 - 2;
+        let max_h = leaf.y2 - leaf.y1 - 2;
+        let w = rng.range(MIN_ROOM, max_w + 1);
+        let h = rng.range(MIN_ROOM, max_h + 1);
+        let x = leaf.x1 + 1 + rng.range(0, max_w - w + 1);
+        let y = leaf.y1 + 1 + rng.range(0, max_h - h + 1);
+
+        let room = Rect::new(x, y, w, h);
+        self.map.apply_room(&room);
+        self.map.rooms.push(room.clone());
+        self.history.push(self.map.tiles.clone());
+
+        return room;
+    }
+
+    fn connect(&mut self, a: &Rect, b: &Rect, rng: &mut RandomNumberGenerator) {
+        let (a_x, a_y) = a.center();
+        let (b_x, b_y) = b.center();
+
+        if rng.roll_dice(1, 2) == 1 {
+            self.map.apply_tunnel_h(a_x, b_x, a_y);
+            self.map.apply_tunnel_v(a_y, b_y, b_x);
+        } else {
+            self.map.apply_tunnel_v(a_y, b_y, a_x);
+            self.map.apply_tunnel_h(a_x, b_x, b_y);
+        }
+        self.history.push(self.map.tiles.clone());
+    }
+}
+
+impl MapBuilder for BspBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let root = Rect::new(1, 1, self.map.width - 2, self.map.height - 2);
+        self.partition(root, rng);
+
+        let (stairs_x, stairs_y) = self.map.rooms[self.map.rooms.len() - 1].center();
+        let stairs_idx = self.map.xy_idx(stairs_x, stairs_y);
+        self.map.tiles[stairs_idx] = TileType::DownStairs;
+
+        let (start_x, start_y) = self.map.rooms[0].center();
+        self.starting_position = Point::new(start_x, start_y);
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, _rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return Vec::new();
+    }
+
+    fn get_spawn_rooms(&self) -> Vec<Rect> {
+        return self.map.rooms.iter().skip(1).cloned().collect();
+    }
+}
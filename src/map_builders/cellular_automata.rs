@@ -0,0 +1,93 @@
+use super::{cull_unreachable, nearest_floor, voronoi_regions, Map, MapBuilder, TileType};
+use rltk::{Point, RandomNumberGenerator};
+
+const FLOOR_CHANCE: i32 = 45;
+const SMOOTHING_PASSES: i32 = 15;
+
+pub struct CellularAutomataBuilder {
+    map: Map,
+    starting_position: Point,
+    history: Vec<Vec<TileType>>,
+}
+
+impl CellularAutomataBuilder {
+    pub fn new(width: i32, height: i32, depth: i32) -> CellularAutomataBuilder {
+        return CellularAutomataBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(width / 2, height / 2),
+            history: Vec::new(),
+        };
+    }
+
+    fn smooth(&mut self) {
+        let map = &mut self.map;
+        let mut new_tiles = map.tiles.clone();
+
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let mut walls = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx != 0 || dy != 0)
+                            && map.tiles[map.xy_idx(x + dx, y + dy)] == TileType::Wall
+                        {
+                            walls += 1;
+                        }
+                    }
+                }
+
+                let idx = map.xy_idx(x, y);
+                if walls > 4 || walls == 0 {
+                    new_tiles[idx] = TileType::Wall;
+                } else {
+                    new_tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+
+        map.tiles = new_tiles;
+    }
+}
+
+impl MapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let (width, height) = (self.map.width, self.map.height);
+
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let idx = self.map.xy_idx(x, y);
+                if rng.roll_dice(1, 100) <= FLOOR_CHANCE {
+                    self.map.tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+
+        self.history.push(self.map.tiles.clone());
+
+        for _i in 0..SMOOTHING_PASSES {
+            self.smooth();
+            self.history.push(self.map.tiles.clone());
+        }
+
+        self.starting_position = nearest_floor(&self.map, self.starting_position);
+        let exit_idx = cull_unreachable(&mut self.map, self.starting_position);
+        self.history.push(self.map.tiles.clone());
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return voronoi_regions(&self.map, self.starting_position, rng);
+    }
+}
@@ -0,0 +1,103 @@
+use super::{Map, TileType};
+use rltk::{Algorithm2D, DijkstraMap, Point};
+
+const MAX_FLOOD_DEPTH: f32 = 2000.0;
+
+/// Number of generation steps between history snapshots in builders that
+/// record their progress incrementally.
+pub const SNAPSHOT_INTERVAL: i32 = 10;
+
+/// Walls off every open tile that cannot be reached from `start` and
+/// returns the index of the reachable tile farthest away from it.
+pub fn cull_unreachable(map: &mut Map, start: Point) -> usize {
+    map.populate_blocked();
+
+    let start_idx = map.xy_idx(start.x, start.y);
+    let dijkstra = DijkstraMap::new(
+        map.width,
+        map.height,
+        &[start_idx as i32],
+        &*map,
+        MAX_FLOOD_DEPTH,
+    );
+
+    let mut farthest_idx = start_idx;
+    let mut farthest_dist = 0.0f32;
+    for (idx, tile) in map.tiles.iter_mut().enumerate() {
+        if *tile == TileType::Wall {
+            continue;
+        }
+
+        let dist = dijkstra.map[idx];
+        if dist == std::f32::MAX {
+            *tile = TileType::Wall;
+        } else if dist > farthest_dist {
+            farthest_idx = idx;
+            farthest_dist = dist;
+        }
+    }
+
+    map.populate_blocked();
+    return farthest_idx;
+}
+
+/// Returns the floor tile closest to `target`, for placing the player on
+/// maps whose layout is not known in advance.
+pub fn nearest_floor(map: &Map, target: Point) -> Point {
+    let mut best = target;
+    let mut best_dist = std::f32::MAX;
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if *tile != TileType::Floor {
+            continue;
+        }
+
+        let pt = map.index_to_point2d(idx as i32);
+        let dist = rltk::DistanceAlg::PythagorasSquared.distance2d(pt, target);
+        if dist < best_dist {
+            best = pt;
+            best_dist = dist;
+        }
+    }
+
+    return best;
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum Symmetry {
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+/// Carves a floor tile, mirroring it across the map's centre lines as
+/// requested by `symmetry`.
+pub fn paint(map: &mut Map, symmetry: Symmetry, x: i32, y: i32) {
+    let mirror_x = map.width - 1 - x;
+    let mirror_y = map.height - 1 - y;
+
+    let mut points = vec![(x, y)];
+    match symmetry {
+        Symmetry::None => {}
+        Symmetry::Horizontal => points.push((mirror_x, y)),
+        Symmetry::Vertical => points.push((x, mirror_y)),
+        Symmetry::Both => {
+            points.push((mirror_x, y));
+            points.push((x, mirror_y));
+            points.push((mirror_x, mirror_y));
+        }
+    }
+
+    for (px, py) in points.iter() {
+        let idx = map.xy_idx(*px, *py);
+        map.tiles[idx] = TileType::Floor;
+    }
+}
+
+pub fn floor_count(map: &Map) -> usize {
+    return map
+        .tiles
+        .iter()
+        .filter(|tile| **tile == TileType::Floor)
+        .count();
+}
@@ -0,0 +1,162 @@
+use super::{
+    cull_unreachable, floor_count, paint, voronoi_regions, Map, MapBuilder, Symmetry, TileType,
+    SNAPSHOT_INTERVAL,
+};
+use rltk::{Point, RandomNumberGenerator};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum DlaAlgorithm {
+    WalkInwards,
+    WalkOutwards,
+    CentralAttractor,
+}
+
+#[derive(Copy, Clone)]
+pub struct DlaSettings {
+    pub algorithm: DlaAlgorithm,
+    pub floor_percent: f32,
+    pub symmetry: Symmetry,
+}
+
+impl DlaSettings {
+    pub fn walk_inwards() -> DlaSettings {
+        return DlaSettings {
+            algorithm: DlaAlgorithm::WalkInwards,
+            floor_percent: 0.25,
+            symmetry: Symmetry::None,
+        };
+    }
+
+    pub fn walk_outwards() -> DlaSettings {
+        return DlaSettings {
+            algorithm: DlaAlgorithm::WalkOutwards,
+            floor_percent: 0.25,
+            symmetry: Symmetry::Vertical,
+        };
+    }
+
+    pub fn central_attractor() -> DlaSettings {
+        return DlaSettings {
+            algorithm: DlaAlgorithm::CentralAttractor,
+            floor_percent: 0.25,
+            symmetry: Symmetry::Horizontal,
+        };
+    }
+}
+
+pub struct DlaBuilder {
+    map: Map,
+    starting_position: Point,
+    history: Vec<Vec<TileType>>,
+    settings: DlaSettings,
+}
+
+impl DlaBuilder {
+    pub fn new(width: i32, height: i32, depth: i32, settings: DlaSettings) -> DlaBuilder {
+        return DlaBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(width / 2, height / 2),
+            history: Vec::new(),
+            settings: settings,
+        };
+    }
+
+    fn is_floor(&self, pt: Point) -> bool {
+        return self.map.tiles[self.map.xy_idx(pt.x, pt.y)] == TileType::Floor;
+    }
+
+    fn random_interior(&self, rng: &mut RandomNumberGenerator) -> Point {
+        return Point::new(
+            rng.roll_dice(1, self.map.width - 4) + 1,
+            rng.roll_dice(1, self.map.height - 4) + 1,
+        );
+    }
+
+    fn stagger(&self, pos: &mut Point, rng: &mut RandomNumberGenerator) {
+        match rng.roll_dice(1, 4) {
+            1 => pos.x = std::cmp::max(pos.x - 1, 2),
+            2 => pos.x = std::cmp::min(pos.x + 1, self.map.width - 3),
+            3 => pos.y = std::cmp::max(pos.y - 1, 2),
+            _ => pos.y = std::cmp::min(pos.y + 1, self.map.height - 3),
+        }
+    }
+
+    /// Moves one particle until it touches the existing floor and returns
+    /// the wall tile it should be frozen into.
+    fn release_particle(&self, rng: &mut RandomNumberGenerator) -> Point {
+        let center = self.starting_position;
+
+        match self.settings.algorithm {
+            DlaAlgorithm::WalkInwards => {
+                let mut pos = self.random_interior(rng);
+                let mut prev = pos;
+                while !self.is_floor(pos) {
+                    prev = pos;
+                    self.stagger(&mut pos, rng);
+                }
+                return prev;
+            }
+            DlaAlgorithm::WalkOutwards => {
+                let mut pos = center;
+                while self.is_floor(pos) {
+                    self.stagger(&mut pos, rng);
+                }
+                return pos;
+            }
+            DlaAlgorithm::CentralAttractor => {
+                let mut pos = self.random_interior(rng);
+                let mut prev = pos;
+                while !self.is_floor(pos) {
+                    prev = pos;
+                    if pos.x != center.x && (pos.y == center.y || rng.roll_dice(1, 2) == 1) {
+                        pos.x += (center.x - pos.x).signum();
+                    } else {
+                        pos.y += (center.y - pos.y).signum();
+                    }
+                }
+                return prev;
+            }
+        }
+    }
+}
+
+impl MapBuilder for DlaBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let start = self.starting_position;
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+            paint(&mut self.map, self.settings.symmetry, start.x + dx, start.y + dy);
+        }
+
+        let target = (self.map.num_tiles as f32 * self.settings.floor_percent) as usize;
+        let mut particles = 0;
+        while floor_count(&self.map) < target {
+            let pt = self.release_particle(rng);
+            paint(&mut self.map, self.settings.symmetry, pt.x, pt.y);
+
+            particles += 1;
+            if particles % SNAPSHOT_INTERVAL == 0 {
+                self.history.push(self.map.tiles.clone());
+            }
+        }
+
+        let exit_idx = cull_unreachable(&mut self.map, self.starting_position);
+        self.history.push(self.map.tiles.clone());
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return voronoi_regions(&self.map, self.starting_position, rng);
+    }
+}
@@ -0,0 +1,126 @@
+use super::{Map, MapBuilder, Rect, TileType};
+use rltk::{Algorithm2D, Point, RandomNumberGenerator};
+
+pub const DOOR_ENTITY: &str = "Door";
+
+/// Wraps another builder and hangs doors wherever a one tile wide corridor
+/// meets one of its rooms.
+pub struct DoorBuilder {
+    inner: Box<dyn MapBuilder>,
+    map: Map,
+    starting_position: Point,
+    spawn_list: Vec<(Point, String)>,
+}
+
+impl DoorBuilder {
+    pub fn new(inner: Box<dyn MapBuilder>) -> DoorBuilder {
+        return DoorBuilder {
+            inner: inner,
+            map: Map::new(0, 0, 0),
+            starting_position: Point::new(0, 0),
+            spawn_list: Vec::new(),
+        };
+    }
+
+    fn is_wall(&self, x: i32, y: i32) -> bool {
+        return self.map.tiles[self.map.xy_idx(x, y)] == TileType::Wall;
+    }
+
+    fn is_doorway(&self, x: i32, y: i32) -> bool {
+        if x < 1 || y < 1 || x > self.map.width - 2 || y > self.map.height - 2 {
+            return false;
+        }
+        if self.map.tiles[self.map.xy_idx(x, y)] != TileType::Floor {
+            return false;
+        }
+
+        let walls_ew = self.is_wall(x - 1, y) && self.is_wall(x + 1, y);
+        let walls_ns = self.is_wall(x, y - 1) && self.is_wall(x, y + 1);
+        return walls_ew != walls_ns;
+    }
+
+    fn place_doors(&mut self) {
+        let mut doors: Vec<Point> = Vec::new();
+
+        for room in self.map.rooms.iter() {
+            let mut ring: Vec<Point> = Vec::new();
+            for x in room.x1 - 1..=room.x2 {
+                ring.push(Point::new(x, room.y1 - 1));
+                ring.push(Point::new(x, room.y2));
+            }
+            for y in room.y1..room.y2 {
+                ring.push(Point::new(room.x1 - 1, y));
+                ring.push(Point::new(room.x2, y));
+            }
+
+            for pt in ring.iter() {
+                let taken = *pt == self.starting_position
+                    || self.spawn_list.iter().any(|(other, _)| other == pt)
+                    || doors.iter().any(|door| {
+                        (door.x - pt.x).abs() <= 1 && (door.y - pt.y).abs() <= 1
+                    });
+
+                if !taken && self.is_doorway(pt.x, pt.y) {
+                    doors.push(*pt);
+                }
+            }
+        }
+
+        for door in doors.iter() {
+            self.spawn_list.push((*door, DOOR_ENTITY.to_string()));
+        }
+    }
+}
+
+impl MapBuilder for DoorBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.inner.build_map(rng);
+        self.map = self.inner.get_map();
+        self.starting_position = self.inner.get_starting_position();
+        self.spawn_list = self.inner.get_spawn_list();
+
+        self.place_doors();
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.inner.get_snapshot_history();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        let doors: Vec<usize> = self
+            .spawn_list
+            .iter()
+            .filter(|(_, name)| name == DOOR_ENTITY)
+            .map(|(pt, _)| self.map.point2d_to_index(*pt) as usize)
+            .collect();
+
+        return self
+            .inner
+            .get_spawn_regions(rng)
+            .into_iter()
+            .map(|region| {
+                region
+                    .into_iter()
+                    .filter(|idx| !doors.contains(idx))
+                    .collect::<Vec<usize>>()
+            })
+            .filter(|region| !region.is_empty())
+            .collect();
+    }
+
+    fn get_spawn_list(&self) -> Vec<(Point, String)> {
+        return self.spawn_list.clone();
+    }
+
+    fn get_spawn_rooms(&self) -> Vec<Rect> {
+        return self.inner.get_spawn_rooms();
+    }
+}
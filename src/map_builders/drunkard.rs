@@ -0,0 +1,139 @@
+use super::{
+    cull_unreachable, floor_count, paint, voronoi_regions, Map, MapBuilder, Symmetry, TileType,
+};
+use rltk::{Algorithm2D, Point, RandomNumberGenerator};
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum DrunkSpawnMode {
+    StartingPoint,
+    Random,
+}
+
+#[derive(Copy, Clone)]
+pub struct DrunkardSettings {
+    pub spawn_mode: DrunkSpawnMode,
+    pub max_walkers: i32,
+    pub lifetime: i32,
+    pub floor_percent: f32,
+    pub symmetry: Symmetry,
+}
+
+impl DrunkardSettings {
+    pub fn open_area() -> DrunkardSettings {
+        return DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::StartingPoint,
+            max_walkers: 400,
+            lifetime: 400,
+            floor_percent: 0.5,
+            symmetry: Symmetry::None,
+        };
+    }
+
+    pub fn winding_passages() -> DrunkardSettings {
+        return DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::Random,
+            max_walkers: 400,
+            lifetime: 100,
+            floor_percent: 0.4,
+            symmetry: Symmetry::None,
+        };
+    }
+
+    pub fn symmetric() -> DrunkardSettings {
+        return DrunkardSettings {
+            spawn_mode: DrunkSpawnMode::Random,
+            max_walkers: 400,
+            lifetime: 100,
+            floor_percent: 0.4,
+            symmetry: Symmetry::Both,
+        };
+    }
+}
+
+pub struct DrunkardsWalkBuilder {
+    map: Map,
+    starting_position: Point,
+    history: Vec<Vec<TileType>>,
+    settings: DrunkardSettings,
+}
+
+impl DrunkardsWalkBuilder {
+    pub fn new(
+        width: i32,
+        height: i32,
+        depth: i32,
+        settings: DrunkardSettings,
+    ) -> DrunkardsWalkBuilder {
+        return DrunkardsWalkBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(width / 2, height / 2),
+            history: Vec::new(),
+            settings: settings,
+        };
+    }
+
+    fn walker_start(&self, rng: &mut RandomNumberGenerator) -> Point {
+        if self.settings.spawn_mode == DrunkSpawnMode::StartingPoint {
+            return self.starting_position;
+        }
+
+        let floors: Vec<usize> = self
+            .map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile == TileType::Floor)
+            .map(|(idx, _)| idx)
+            .collect();
+        let idx = floors[rng.range(0, floors.len())];
+        return self.map.index_to_point2d(idx as i32);
+    }
+}
+
+impl MapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let (width, height) = (self.map.width, self.map.height);
+        let start = self.starting_position;
+        paint(&mut self.map, self.settings.symmetry, start.x, start.y);
+
+        let target = (self.map.num_tiles as f32 * self.settings.floor_percent) as usize;
+        let mut walkers = 0;
+        while floor_count(&self.map) < target && walkers < self.settings.max_walkers {
+            let mut pos = self.walker_start(rng);
+
+            for _step in 0..self.settings.lifetime {
+                paint(&mut self.map, self.settings.symmetry, pos.x, pos.y);
+
+                match rng.roll_dice(1, 4) {
+                    1 => pos.x = std::cmp::max(pos.x - 1, 2),
+                    2 => pos.x = std::cmp::min(pos.x + 1, width - 3),
+                    3 => pos.y = std::cmp::max(pos.y - 1, 2),
+                    _ => pos.y = std::cmp::min(pos.y + 1, height - 3),
+                }
+            }
+
+            walkers += 1;
+            self.history.push(self.map.tiles.clone());
+        }
+
+        let exit_idx = cull_unreachable(&mut self.map, self.starting_position);
+        self.history.push(self.map.tiles.clone());
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return voronoi_regions(&self.map, self.starting_position, rng);
+    }
+}
@@ -0,0 +1,96 @@
+use super::{cull_unreachable, voronoi_regions, Map, MapBuilder, TileType, SNAPSHOT_INTERVAL};
+use rltk::{Algorithm2D, Point, RandomNumberGenerator};
+
+pub struct MazeBuilder {
+    map: Map,
+    starting_position: Point,
+    history: Vec<Vec<TileType>>,
+}
+
+impl MazeBuilder {
+    pub fn new(width: i32, height: i32, depth: i32) -> MazeBuilder {
+        return MazeBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(1, 1),
+            history: Vec::new(),
+        };
+    }
+
+    fn carve(&mut self, cell_x: i32, cell_y: i32, dx: i32, dy: i32) {
+        let (x, y) = (cell_x * 2 + 1, cell_y * 2 + 1);
+        for step in 0..3 {
+            let idx = self.map.xy_idx(x + dx * step, y + dy * step);
+            self.map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+impl MapBuilder for MazeBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        // Cells live on odd coordinates; the tiles between them are the walls
+        // a passage can be knocked through.
+        let grid_w = (self.map.width - 1) / 2;
+        let grid_h = (self.map.height - 1) / 2;
+        let mut visited = vec![false; (grid_w * grid_h) as usize];
+
+        let mut carved = 0;
+        let mut stack: Vec<(i32, i32)> = vec![(0, 0)];
+        visited[0] = true;
+        let first = self.map.xy_idx(1, 1);
+        self.map.tiles[first] = TileType::Floor;
+
+        while let Some(&(cx, cy)) = stack.last() {
+            let mut neighbors: Vec<(i32, i32)> = Vec::new();
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+                let (nx, ny) = (cx + dx, cy + dy);
+                if nx >= 0
+                    && nx < grid_w
+                    && ny >= 0
+                    && ny < grid_h
+                    && !visited[(ny * grid_w + nx) as usize]
+                {
+                    neighbors.push((*dx, *dy));
+                }
+            }
+
+            if neighbors.is_empty() {
+                stack.pop();
+                continue;
+            }
+
+            let (dx, dy) = neighbors[rng.range(0, neighbors.len())];
+            self.carve(cx, cy, dx, dy);
+            visited[((cy + dy) * grid_w + cx + dx) as usize] = true;
+            stack.push((cx + dx, cy + dy));
+
+            carved += 1;
+            if carved % SNAPSHOT_INTERVAL == 0 {
+                self.history.push(self.map.tiles.clone());
+            }
+        }
+
+        // A perfect maze is a tree, so the far end of the longest path from
+        // any cell is one end of the maze's diameter.
+        let far_idx = cull_unreachable(&mut self.map, Point::new(1, 1));
+        self.starting_position = self.map.index_to_point2d(far_idx as i32);
+
+        let exit_idx = cull_unreachable(&mut self.map, self.starting_position);
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return voronoi_regions(&self.map, self.starting_position, rng);
+    }
+}
@@ -0,0 +1,215 @@
+use super::map::*;
+use super::raws::RawMaster;
+use super::util::*;
+use rltk::{Point, RandomNumberGenerator};
+use std::sync::Arc;
+
+mod common;
+pub use common::*;
+
+mod spawn_region;
+pub use spawn_region::*;
+
+mod simple_map;
+pub use simple_map::*;
+
+mod random_walls;
+pub use random_walls::*;
+
+mod bsp;
+pub use bsp::*;
+
+mod cellular_automata;
+pub use cellular_automata::*;
+
+mod drunkard;
+pub use drunkard::*;
+
+mod dla;
+pub use dla::*;
+
+mod maze;
+pub use maze::*;
+
+mod prefab;
+pub use prefab::*;
+
+mod wfc;
+pub use wfc::*;
+
+mod doors;
+pub use doors::*;
+
+pub trait MapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator);
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> Point;
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>>;
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>>;
+
+    fn get_spawn_list(&self) -> Vec<(Point, String)> {
+        return Vec::new();
+    }
+
+    /// Rectangular rooms to fill with `populate_room`, for builders that lay
+    /// the level out as rooms rather than spawn regions.
+    fn get_spawn_rooms(&self) -> Vec<Rect> {
+        return Vec::new();
+    }
+}
+
+type BuilderFn = fn(i32, i32, i32) -> Box<dyn MapBuilder>;
+type PrefabBuilderFn = fn(i32, i32, i32, Arc<PrefabLibrary>) -> Box<dyn MapBuilder>;
+
+fn rooms_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    return Box::new(SimpleMapBuilder::new(width, height, depth));
+}
+
+fn bsp_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    return Box::new(BspBuilder::new(width, height, depth));
+}
+
+fn caves_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    return Box::new(CellularAutomataBuilder::new(width, height, depth));
+}
+
+fn drunkard_open_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    let settings = DrunkardSettings::open_area();
+    return Box::new(DrunkardsWalkBuilder::new(width, height, depth, settings));
+}
+
+fn drunkard_winding_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    let settings = DrunkardSettings::winding_passages();
+    return Box::new(DrunkardsWalkBuilder::new(width, height, depth, settings));
+}
+
+fn drunkard_symmetric_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    let settings = DrunkardSettings::symmetric();
+    return Box::new(DrunkardsWalkBuilder::new(width, height, depth, settings));
+}
+
+fn dla_inwards_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    return Box::new(DlaBuilder::new(width, height, depth, DlaSettings::walk_inwards()));
+}
+
+fn dla_outwards_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    return Box::new(DlaBuilder::new(width, height, depth, DlaSettings::walk_outwards()));
+}
+
+fn dla_attractor_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    let settings = DlaSettings::central_attractor();
+    return Box::new(DlaBuilder::new(width, height, depth, settings));
+}
+
+fn maze_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    return Box::new(MazeBuilder::new(width, height, depth));
+}
+
+fn wfc_caves_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    let source = caves_builder(width, height, depth);
+    return Box::new(WaveFunctionCollapseBuilder::new(width, height, depth, source));
+}
+
+fn random_walls_builder(width: i32, height: i32, depth: i32) -> Box<dyn MapBuilder> {
+    return Box::new(RandomWallsBuilder::new(width, height, depth));
+}
+
+fn prefab_level_builder(
+    width: i32,
+    height: i32,
+    depth: i32,
+    prefabs: Arc<PrefabLibrary>,
+) -> Box<dyn MapBuilder> {
+    return Box::new(PrefabLevelBuilder::new(width, height, depth, prefabs));
+}
+
+fn wfc_prefab_builder(
+    width: i32,
+    height: i32,
+    depth: i32,
+    prefabs: Arc<PrefabLibrary>,
+) -> Box<dyn MapBuilder> {
+    let source = prefab_level_builder(width, height, depth, prefabs);
+    return Box::new(WaveFunctionCollapseBuilder::new(width, height, depth, source));
+}
+
+const BUILDERS: &[(&str, BuilderFn)] = &[
+    ("rooms", rooms_builder),
+    ("random_walls", random_walls_builder),
+    ("bsp", bsp_builder),
+    ("caves", caves_builder),
+    ("drunkard_open", drunkard_open_builder),
+    ("drunkard_winding", drunkard_winding_builder),
+    ("drunkard_symmetric", drunkard_symmetric_builder),
+    ("dla_inwards", dla_inwards_builder),
+    ("dla_outwards", dla_outwards_builder),
+    ("dla_attractor", dla_attractor_builder),
+    ("maze", maze_builder),
+    ("wfc_caves", wfc_caves_builder),
+];
+
+/// Builders that are built from the prefab library, kept apart so the rest
+/// do not have to take it.
+const PREFAB_BUILDERS: &[(&str, PrefabBuilderFn)] = &[
+    ("prefab_level", prefab_level_builder),
+    ("wfc_prefab", wfc_prefab_builder),
+];
+
+pub fn builder_names() -> Vec<&'static str> {
+    return BUILDERS
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(PREFAB_BUILDERS.iter().map(|(name, _)| *name))
+        .collect();
+}
+
+pub fn builder_by_name(
+    name: &str,
+    width: i32,
+    height: i32,
+    depth: i32,
+    prefabs: &Arc<PrefabLibrary>,
+) -> Option<Box<dyn MapBuilder>> {
+    for (builder_name, builder) in BUILDERS.iter() {
+        if *builder_name == name {
+            return Some(builder(width, height, depth));
+        }
+    }
+    for (builder_name, builder) in PREFAB_BUILDERS.iter() {
+        if *builder_name == name {
+            return Some(builder(width, height, depth, Arc::clone(prefabs)));
+        }
+    }
+
+    return None;
+}
+
+pub fn random_builder(
+    width: i32,
+    height: i32,
+    depth: i32,
+    prefabs: &Arc<PrefabLibrary>,
+    rng: &mut RandomNumberGenerator,
+) -> Box<dyn MapBuilder> {
+    let roll = (rng.roll_dice(1, (BUILDERS.len() + PREFAB_BUILDERS.len()) as i32) - 1) as usize;
+    if roll < BUILDERS.len() {
+        let (_, builder) = BUILDERS[roll];
+        return builder(width, height, depth);
+    }
+
+    let (_, builder) = PREFAB_BUILDERS[roll - BUILDERS.len()];
+    return builder(width, height, depth, Arc::clone(prefabs));
+}
+
+/// Checks that every entity the builders may spawn by name exists in the raws.
+pub fn check_raws(raws: &RawMaster, prefabs: &PrefabLibrary) -> Result<(), String> {
+    prefabs.check_entities(raws)?;
+
+    if !raws.contains(DOOR_ENTITY) {
+        return Err(format!(
+            "Map builders need a '{}' entity in the raws.",
+            DOOR_ENTITY
+        ));
+    }
+    return Ok(());
+}
@@ -0,0 +1,456 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::Arc;
+
+use super::{rect_tiles, Map, MapBuilder, RawMaster, Rect, SimpleMapBuilder, TileType};
+use rltk::{Point, RandomNumberGenerator};
+use serde::Deserialize;
+
+const ROOM_PREFAB_CHANCE: i32 = 4;
+const VAULT_CHANCE: i32 = 2;
+
+#[derive(Deserialize, PartialEq, Copy, Clone, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PrefabPlacement {
+    Level,
+    Room,
+    Vault,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Prefab {
+    pub name: String,
+    pub placement: PrefabPlacement,
+    pub min_depth: i32,
+    pub max_depth: i32,
+    pub template: Vec<String>,
+}
+
+impl Prefab {
+    pub fn width(&self) -> i32 {
+        return self.template[0].chars().count() as i32;
+    }
+
+    pub fn height(&self) -> i32 {
+        return self.template.len() as i32;
+    }
+
+    fn rows(&self) -> Vec<Vec<char>> {
+        return self.template.iter().map(|row| row.chars().collect()).collect();
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct PrefabLibrary {
+    #[serde(default)]
+    pub legend: HashMap<String, String>,
+    #[serde(default)]
+    pub prefabs: Vec<Prefab>,
+}
+
+impl PrefabLibrary {
+    pub fn load(path: &str) -> Result<PrefabLibrary, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Unable to read {}: {}", path, e))?;
+        let library: PrefabLibrary = serde_json::from_str(&contents)
+            .map_err(|e| format!("Unable to parse {}: {}", path, e))?;
+
+        return library
+            .validate()
+            .map(|_| library)
+            .map_err(|e| format!("Invalid prefabs in {}:\n{}", path, e));
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        for symbol in self.legend.keys() {
+            if symbol.chars().count() != 1 {
+                errors.push(format!("legend symbol '{}' must be one character", symbol));
+            } else if "#.@>".contains(symbol.as_str()) {
+                errors.push(format!("legend symbol '{}' is reserved", symbol));
+            }
+        }
+
+        for prefab in self.prefabs.iter() {
+            let ctx = format!("prefab '{}'", prefab.name);
+            if prefab.template.is_empty() || prefab.width() == 0 {
+                errors.push(format!("{}: template is empty", ctx));
+                continue;
+            }
+            if prefab.min_depth > prefab.max_depth {
+                errors.push(format!("{}: min_depth is greater than max_depth", ctx));
+            }
+
+            let rows = prefab.rows();
+            if rows.iter().any(|row| row.len() as i32 != prefab.width()) {
+                errors.push(format!("{}: template rows must all be the same width", ctx));
+                continue;
+            }
+
+            let mut starts = 0;
+            let mut exits = 0;
+            for (y, row) in rows.iter().enumerate() {
+                for (x, glyph) in row.iter().enumerate() {
+                    match glyph {
+                        '#' | '.' => {}
+                        '@' => starts += 1,
+                        '>' => exits += 1,
+                        _ if self.entity_for(*glyph).is_some() => {}
+                        _ => errors.push(format!("{}: unknown symbol '{}'", ctx, glyph)),
+                    }
+
+                    let on_edge = x == 0 || y == 0 || x == row.len() - 1 || y == rows.len() - 1;
+                    if prefab.placement == PrefabPlacement::Room && on_edge && *glyph == '#' {
+                        errors.push(format!("{}: room prefabs must have an open border", ctx));
+                    }
+                }
+            }
+
+            if prefab.placement == PrefabPlacement::Level {
+                if starts != 1 || exits != 1 {
+                    errors.push(format!("{}: levels need exactly one '@' and one '>'", ctx));
+                }
+            } else if starts != 0 || exits != 0 {
+                errors.push(format!("{}: only levels may contain '@' or '>'", ctx));
+            }
+        }
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+        return Err(errors.join("\n"));
+    }
+
+    pub fn check_entities(&self, raws: &RawMaster) -> Result<(), String> {
+        let mut missing: Vec<String> = self
+            .legend
+            .iter()
+            .filter(|(_, name)| !raws.contains(name))
+            .map(|(symbol, name)| format!("legend symbol '{}' names unknown entity '{}'", symbol, name))
+            .collect();
+        missing.sort();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+        return Err(format!("Invalid prefab legend:\n{}", missing.join("\n")));
+    }
+
+    fn entity_for(&self, glyph: char) -> Option<&String> {
+        return self.legend.get(&glyph.to_string());
+    }
+
+    fn pick<F>(
+        &self,
+        placement: PrefabPlacement,
+        depth: i32,
+        fits: F,
+        rng: &mut RandomNumberGenerator,
+    ) -> Option<&Prefab>
+    where
+        F: Fn(&Prefab) -> bool,
+    {
+        let candidates: Vec<&Prefab> = self
+            .prefabs
+            .iter()
+            .filter(|p| p.placement == placement && depth >= p.min_depth && depth <= p.max_depth)
+            .filter(|p| fits(p))
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+        return Some(candidates[rng.range(0, candidates.len())]);
+    }
+
+    /// Copies a prefab onto the map with its top left corner at (x, y),
+    /// queueing its entity symbols for spawning.
+    fn stamp(
+        &self,
+        map: &mut Map,
+        prefab: &Prefab,
+        x: i32,
+        y: i32,
+        spawn_list: &mut Vec<(Point, String)>,
+    ) -> Option<Point> {
+        let mut start = None;
+
+        for (dy, row) in prefab.rows().iter().enumerate() {
+            for (dx, glyph) in row.iter().enumerate() {
+                let pt = Point::new(x + dx as i32, y + dy as i32);
+                let idx = map.xy_idx(pt.x, pt.y);
+
+                map.tiles[idx] = match glyph {
+                    '#' => TileType::Wall,
+                    '>' => TileType::DownStairs,
+                    _ => TileType::Floor,
+                };
+
+                if *glyph == '@' {
+                    start = Some(pt);
+                } else if let Some(name) = self.entity_for(*glyph) {
+                    spawn_list.push((pt, name.clone()));
+                }
+            }
+        }
+
+        return start;
+    }
+}
+
+/// Builds an entire level from a hand-authored `level` prefab, or falls back
+/// to the rooms builder when no level prefab suits the depth.
+pub struct PrefabLevelBuilder {
+    library: Arc<PrefabLibrary>,
+    fallback: Option<Box<dyn MapBuilder>>,
+    map: Map,
+    starting_position: Point,
+    spawn_list: Vec<(Point, String)>,
+    history: Vec<Vec<TileType>>,
+}
+
+impl PrefabLevelBuilder {
+    pub fn new(
+        width: i32,
+        height: i32,
+        depth: i32,
+        library: Arc<PrefabLibrary>,
+    ) -> PrefabLevelBuilder {
+        return PrefabLevelBuilder {
+            library: library,
+            fallback: None,
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(0, 0),
+            spawn_list: Vec::new(),
+            history: Vec::new(),
+        };
+    }
+}
+
+impl MapBuilder for PrefabLevelBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let library = &self.library;
+        let (width, height) = (self.map.width, self.map.height);
+        let fits = |p: &Prefab| p.width() <= width - 2 && p.height() <= height - 2;
+
+        let prefab = match library.pick(PrefabPlacement::Level, self.map.depth, fits, rng) {
+            Some(prefab) => prefab,
+            None => {
+                let mut fallback = SimpleMapBuilder::new(width, height, self.map.depth);
+                fallback.build_map(rng);
+                self.map = fallback.get_map();
+                self.starting_position = fallback.get_starting_position();
+                self.history = fallback.get_snapshot_history();
+                self.fallback = Some(Box::new(fallback));
+                return;
+            }
+        };
+
+        let x = (width - prefab.width()) / 2;
+        let y = (height - prefab.height()) / 2;
+        self.starting_position = library
+            .stamp(&mut self.map, prefab, x, y, &mut self.spawn_list)
+            .expect("Prefabs were not validated.");
+        self.history.push(self.map.tiles.clone());
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        match &self.fallback {
+            Some(fallback) => return fallback.get_spawn_regions(rng),
+            None => return Vec::new(),
+        }
+    }
+
+    fn get_spawn_list(&self) -> Vec<(Point, String)> {
+        return self.spawn_list.clone();
+    }
+
+    fn get_spawn_rooms(&self) -> Vec<Rect> {
+        match &self.fallback {
+            Some(fallback) => return fallback.get_spawn_rooms(),
+            None => return Vec::new(),
+        }
+    }
+}
+
+/// Wraps another builder and decorates its output with prefab rooms and
+/// vaults.
+pub struct PrefabBuilder {
+    inner: Box<dyn MapBuilder>,
+    library: Arc<PrefabLibrary>,
+    map: Map,
+    starting_position: Point,
+    spawn_list: Vec<(Point, String)>,
+    history: Vec<Vec<TileType>>,
+    stamped_tiles: HashSet<usize>,
+}
+
+impl PrefabBuilder {
+    pub fn new(inner: Box<dyn MapBuilder>, library: Arc<PrefabLibrary>) -> PrefabBuilder {
+        return PrefabBuilder {
+            inner: inner,
+            library: library,
+            map: Map::new(0, 0, 0),
+            starting_position: Point::new(0, 0),
+            spawn_list: Vec::new(),
+            history: Vec::new(),
+            stamped_tiles: HashSet::new(),
+        };
+    }
+
+    fn is_stamped(&self, room: &Rect) -> bool {
+        return rect_tiles(&self.map, room)
+            .iter()
+            .any(|idx| self.stamped_tiles.contains(idx));
+    }
+
+    fn mark_stamped(&mut self, x: i32, y: i32, prefab: &Prefab) {
+        self.history.push(self.map.tiles.clone());
+        for ty in y..y + prefab.height() {
+            for tx in x..x + prefab.width() {
+                self.stamped_tiles.insert(self.map.xy_idx(tx, ty));
+            }
+        }
+    }
+
+    fn replace_rooms(&mut self, library: &PrefabLibrary, rng: &mut RandomNumberGenerator) {
+        let start = self.starting_position;
+        let exit = self.map.find_tile(TileType::DownStairs);
+
+        for room in self.map.rooms.clone().iter() {
+            let contains = |pt: Point| pt.x >= room.x1 && pt.x < room.x2 && pt.y >= room.y1 && pt.y < room.y2;
+            if contains(start) || exit.map_or(false, contains) {
+                continue;
+            }
+            if rng.roll_dice(1, ROOM_PREFAB_CHANCE) != 1 {
+                continue;
+            }
+
+            let (room_w, room_h) = (room.x2 - room.x1, room.y2 - room.y1);
+            let fits = |p: &Prefab| p.width() <= room_w && p.height() <= room_h;
+            if let Some(prefab) = library.pick(PrefabPlacement::Room, self.map.depth, fits, rng) {
+                let x = room.x1 + (room_w - prefab.width()) / 2;
+                let y = room.y1 + (room_h - prefab.height()) / 2;
+                library.stamp(&mut self.map, prefab, x, y, &mut self.spawn_list);
+                self.mark_stamped(x, y, prefab);
+            }
+        }
+    }
+
+    fn place_vault(&mut self, library: &PrefabLibrary, rng: &mut RandomNumberGenerator) {
+        if rng.roll_dice(1, VAULT_CHANCE) != 1 {
+            return;
+        }
+
+        let (width, height) = (self.map.width, self.map.height);
+        let vault = match library.pick(PrefabPlacement::Vault, self.map.depth, |_| true, rng) {
+            Some(vault) => vault,
+            None => return,
+        };
+
+        // Vaults need a ring of open floor around them, so anything that used
+        // to path through the space can still walk around the vault.
+        let start = self.starting_position;
+        let mut spots: Vec<(i32, i32)> = Vec::new();
+        for y in 1..height - vault.height() - 1 {
+            for x in 1..width - vault.width() - 1 {
+                let (x1, y1) = (x - 1, y - 1);
+                let (x2, y2) = (x + vault.width() + 1, y + vault.height() + 1);
+                let inside = |pt: &Point| pt.x >= x1 && pt.x < x2 && pt.y >= y1 && pt.y < y2;
+                if inside(&start) || self.spawn_list.iter().any(|(pt, _)| inside(pt)) {
+                    continue;
+                }
+
+                let open = (y1..y2).all(|ty| {
+                    (x1..x2).all(|tx| self.map.tiles[self.map.xy_idx(tx, ty)] == TileType::Floor)
+                });
+                if open {
+                    spots.push((x, y));
+                }
+            }
+        }
+
+        if spots.is_empty() {
+            return;
+        }
+        let (x, y) = spots[rng.range(0, spots.len())];
+        library.stamp(&mut self.map, vault, x, y, &mut self.spawn_list);
+        self.mark_stamped(x, y, vault);
+    }
+}
+
+impl MapBuilder for PrefabBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.inner.build_map(rng);
+        self.map = self.inner.get_map();
+        self.starting_position = self.inner.get_starting_position();
+        self.spawn_list = self.inner.get_spawn_list();
+        self.history = self.inner.get_snapshot_history();
+
+        let library = Arc::clone(&self.library);
+        self.replace_rooms(&library, rng);
+        self.place_vault(&library, rng);
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        // Rooms that had a prefab stamped into them are handed out as regions
+        // instead, so the stamped tiles can be left out.
+        let stamped_rooms = self
+            .inner
+            .get_spawn_rooms()
+            .into_iter()
+            .filter(|room| self.is_stamped(room))
+            .map(|room| rect_tiles(&self.map, &room));
+
+        return self
+            .inner
+            .get_spawn_regions(rng)
+            .into_iter()
+            .chain(stamped_rooms)
+            .map(|region| {
+                region
+                    .into_iter()
+                    .filter(|idx| !self.stamped_tiles.contains(idx))
+                    .collect::<Vec<usize>>()
+            })
+            .filter(|region| !region.is_empty())
+            .collect();
+    }
+
+    fn get_spawn_list(&self) -> Vec<(Point, String)> {
+        return self.spawn_list.clone();
+    }
+    fn get_spawn_rooms(&self) -> Vec<Rect> {
+        return self
+            .inner
+            .get_spawn_rooms()
+            .into_iter()
+            .filter(|room| !self.is_stamped(room))
+            .collect();
+    }
+}
@@ -0,0 +1,66 @@
+use super::{
+    cull_unreachable, voronoi_regions, Map, MapBuilder, Rect, TileType, SNAPSHOT_INTERVAL,
+};
+use rltk::{Point, RandomNumberGenerator};
+
+pub struct RandomWallsBuilder {
+    map: Map,
+    starting_position: Point,
+    history: Vec<Vec<TileType>>,
+}
+
+impl RandomWallsBuilder {
+    pub fn new(width: i32, height: i32, depth: i32) -> RandomWallsBuilder {
+        return RandomWallsBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(width / 2, height / 2),
+            history: Vec::new(),
+        };
+    }
+}
+
+impl MapBuilder for RandomWallsBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        let map = &mut self.map;
+        let (width, height) = (map.width, map.height);
+
+        map.rooms.push(Rect::new(1, 1, width - 2, height - 2));
+        map.apply_room(&map.rooms[0].clone());
+        self.history.push(map.tiles.clone());
+
+        let num_walls = width * height / 10;
+        let start_idx = map.xy_idx(self.starting_position.x, self.starting_position.y);
+        for i in 0..num_walls {
+            let x = rng.roll_dice(1, width - 2);
+            let y = rng.roll_dice(1, height - 2);
+            let idx = map.xy_idx(x, y);
+
+            if idx != start_idx {
+                map.tiles[idx] = TileType::Wall;
+            }
+            if i % SNAPSHOT_INTERVAL == 0 {
+                self.history.push(map.tiles.clone());
+            }
+        }
+
+        let exit_idx = cull_unreachable(&mut self.map, self.starting_position);
+        self.history.push(self.map.tiles.clone());
+        self.map.tiles[exit_idx] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return voronoi_regions(&self.map, self.starting_position, rng);
+    }
+}
@@ -0,0 +1,86 @@
+use super::{Map, MapBuilder, Rect, TileType};
+use rltk::{Point, RandomNumberGenerator};
+
+pub struct SimpleMapBuilder {
+    map: Map,
+    starting_position: Point,
+    history: Vec<Vec<TileType>>,
+}
+
+impl SimpleMapBuilder {
+    pub fn new(width: i32, height: i32, depth: i32) -> SimpleMapBuilder {
+        return SimpleMapBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(0, 0),
+            history: Vec::new(),
+        };
+    }
+}
+
+impl MapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        const MAX_ROOMS: i32 = 32;
+        const MIN_SIZE: i32 = 5;
+        const MAX_SIZE: i32 = 12;
+
+        let map = &mut self.map;
+        for _i in 0..MAX_ROOMS {
+            let w = rng.range(MIN_SIZE, MAX_SIZE);
+            let h = rng.range(MIN_SIZE, MAX_SIZE);
+            let x = rng.range(1, map.width - w);
+            let y = rng.range(1, map.height - h);
+            let new_room = Rect::new(x, y, w, h);
+            let mut ok = true;
+            for other_room in map.rooms.iter() {
+                if new_room.intersect(other_room) {
+                    ok = false;
+                }
+            }
+            if ok {
+                map.apply_room(&new_room);
+
+                if !map.rooms.is_empty() {
+                    let (new_x, new_y) = new_room.center();
+                    let (prev_x, prev_y) = map.rooms[map.rooms.len() - 1].center();
+                    if rng.roll_dice(1, 2) == 1 {
+                        map.apply_tunnel_h(prev_x, new_x, prev_y);
+                        map.apply_tunnel_v(prev_y, new_y, new_x);
+                    } else {
+                        map.apply_tunnel_v(prev_y, new_y, prev_x);
+                        map.apply_tunnel_h(prev_x, new_x, new_y);
+                    }
+                }
+
+                map.rooms.push(new_room);
+                self.history.push(map.tiles.clone());
+            }
+        }
+
+        let (stairs_x, stairs_y) = map.rooms[map.rooms.len() - 1].center();
+        let stairs_idx = map.xy_idx(stairs_x, stairs_y);
+        map.tiles[stairs_idx] = TileType::DownStairs;
+
+        let (start_x, start_y) = map.rooms[0].center();
+        self.starting_position = Point::new(start_x, start_y);
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, _rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return Vec::new();
+    }
+
+    fn get_spawn_rooms(&self) -> Vec<Rect> {
+        return self.map.rooms.iter().skip(1).cloned().collect();
+    }
+}
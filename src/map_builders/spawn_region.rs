@@ -0,0 +1,63 @@
+use super::{Map, Rect, TileType};
+use rltk::{Algorithm2D, Point, RandomNumberGenerator};
+
+const TILES_PER_REGION: usize = 128;
+
+pub fn rect_tiles(map: &Map, rect: &Rect) -> Vec<usize> {
+    let mut tiles: Vec<usize> = Vec::new();
+    for y in rect.y1..rect.y2 {
+        for x in rect.x1..rect.x2 {
+            tiles.push(map.xy_idx(x, y));
+        }
+    }
+
+    return tiles;
+}
+
+/// Partitions the open floor into Voronoi cells around randomly chosen seed
+/// tiles, dropping the cell the player starts in.
+pub fn voronoi_regions(
+    map: &Map,
+    start: Point,
+    rng: &mut RandomNumberGenerator,
+) -> Vec<Vec<usize>> {
+    let floors: Vec<usize> = map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == TileType::Floor)
+        .map(|(idx, _)| idx)
+        .collect();
+    if floors.is_empty() {
+        return Vec::new();
+    }
+
+    let num_seeds = std::cmp::max(1, floors.len() / TILES_PER_REGION);
+    let mut pool = floors.clone();
+    let mut seeds: Vec<Point> = Vec::new();
+    while seeds.len() < num_seeds {
+        let idx = pool.remove(rng.range(0, pool.len()));
+        seeds.push(map.index_to_point2d(idx as i32));
+    }
+
+    let mut regions: Vec<Vec<usize>> = vec![Vec::new(); seeds.len()];
+    for idx in floors.iter() {
+        let pt = map.index_to_point2d(*idx as i32);
+        let mut nearest = 0;
+        let mut nearest_dist = std::f32::MAX;
+        for (seed, seed_pt) in seeds.iter().enumerate() {
+            let dist = rltk::DistanceAlg::PythagorasSquared.distance2d(pt, *seed_pt);
+            if dist < nearest_dist {
+                nearest = seed;
+                nearest_dist = dist;
+            }
+        }
+        regions[nearest].push(*idx);
+    }
+
+    let start_idx = map.xy_idx(start.x, start.y);
+    return regions
+        .into_iter()
+        .filter(|region| !region.contains(&start_idx))
+        .collect();
+}
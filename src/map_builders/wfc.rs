@@ -0,0 +1,277 @@
+use super::{
+    cull_unreachable, floor_count, nearest_floor, voronoi_regions, Map, MapBuilder, TileType,
+};
+use rltk::{Point, RandomNumberGenerator};
+
+const PATTERN_SIZE: i32 = 4;
+const MAX_ATTEMPTS: i32 = 10;
+
+// Directions index pattern edges: north, east, south, west.
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+struct Pattern {
+    floor: Vec<bool>,
+    weight: i32,
+}
+
+impl Pattern {
+    fn edge(&self, direction: usize) -> Vec<bool> {
+        let last = PATTERN_SIZE - 1;
+        return (0..PATTERN_SIZE)
+            .map(|i| {
+                let (x, y) = match direction {
+                    0 => (i, 0),
+                    1 => (last, i),
+                    2 => (i, last),
+                    _ => (0, i),
+                };
+                self.floor[(y * PATTERN_SIZE + x) as usize]
+            })
+            .collect();
+    }
+}
+
+/// Learns which wall/floor patterns may sit next to each other from the
+/// output of a source builder, then synthesizes a new level from them.
+pub struct WaveFunctionCollapseBuilder {
+    map: Map,
+    starting_position: Point,
+    spawn_list: Vec<(Point, String)>,
+    history: Vec<Vec<TileType>>,
+    source: Box<dyn MapBuilder>,
+}
+
+impl WaveFunctionCollapseBuilder {
+    pub fn new(
+        width: i32,
+        height: i32,
+        depth: i32,
+        source: Box<dyn MapBuilder>,
+    ) -> WaveFunctionCollapseBuilder {
+        return WaveFunctionCollapseBuilder {
+            map: Map::new(width, height, depth),
+            starting_position: Point::new(width / 2, height / 2),
+            spawn_list: Vec::new(),
+            history: Vec::new(),
+            source: source,
+        };
+    }
+
+    fn grid_size(&self) -> (i32, i32) {
+        return (
+            (self.map.width - 2) / PATTERN_SIZE,
+            (self.map.height - 2) / PATTERN_SIZE,
+        );
+    }
+
+    fn extract_patterns(&self, source: &Map) -> Vec<Pattern> {
+        let (grid_w, grid_h) = self.grid_size();
+        let mut patterns: Vec<Pattern> = Vec::new();
+
+        for cy in 0..grid_h {
+            for cx in 0..grid_w {
+                let mut floor: Vec<bool> = Vec::new();
+                for y in 0..PATTERN_SIZE {
+                    for x in 0..PATTERN_SIZE {
+                        let idx = source.xy_idx(1 + cx * PATTERN_SIZE + x, 1 + cy * PATTERN_SIZE + y);
+                        floor.push(source.tiles[idx] != TileType::Wall);
+                    }
+                }
+
+                match patterns.iter_mut().find(|p| p.floor == floor) {
+                    Some(pattern) => pattern.weight += 1,
+                    None => patterns.push(Pattern {
+                        floor: floor,
+                        weight: 1,
+                    }),
+                }
+            }
+        }
+
+        return patterns;
+    }
+
+    /// Collapses every grid cell to a single pattern, or returns None if the
+    /// constraints contradict each other.
+    fn solve(
+        &self,
+        patterns: &[Pattern],
+        compatible: &[Vec<Vec<bool>>],
+        rng: &mut RandomNumberGenerator,
+    ) -> Option<Vec<usize>> {
+        let (grid_w, grid_h) = self.grid_size();
+        let mut options = vec![vec![true; patterns.len()]; (grid_w * grid_h) as usize];
+
+        loop {
+            let mut lowest = usize::max_value();
+            let mut candidates: Vec<usize> = Vec::new();
+            for (cell, cell_options) in options.iter().enumerate() {
+                let count = cell_options.iter().filter(|o| **o).count();
+                if count == 0 {
+                    return None;
+                }
+                if count > 1 && count < lowest {
+                    lowest = count;
+                    candidates.clear();
+                }
+                if count > 1 && count == lowest {
+                    candidates.push(cell);
+                }
+            }
+
+            if candidates.is_empty() {
+                break;
+            }
+
+            let cell = candidates[rng.range(0, candidates.len())];
+            let total: i32 = (0..patterns.len())
+                .filter(|p| options[cell][*p])
+                .map(|p| patterns[p].weight)
+                .sum();
+            let mut roll = rng.roll_dice(1, total);
+            let mut chosen = 0;
+            for p in 0..patterns.len() {
+                if options[cell][p] {
+                    roll -= patterns[p].weight;
+                    if roll <= 0 {
+                        chosen = p;
+                        break;
+                    }
+                }
+            }
+            for p in 0..patterns.len() {
+                options[cell][p] = p == chosen;
+            }
+
+            let mut stack = vec![cell];
+            while let Some(current) = stack.pop() {
+                let (x, y) = (current as i32 % grid_w, current as i32 / grid_w);
+                for (direction, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || nx >= grid_w || ny < 0 || ny >= grid_h {
+                        continue;
+                    }
+
+                    let neighbor = (ny * grid_w + nx) as usize;
+                    let mut changed = false;
+                    for b in 0..patterns.len() {
+                        if !options[neighbor][b] {
+                            continue;
+                        }
+                        let supported = (0..patterns.len())
+                            .any(|a| options[current][a] && compatible[a][direction][b]);
+                        if !supported {
+                            options[neighbor][b] = false;
+                            changed = true;
+                        }
+                    }
+
+                    if changed {
+                        if !options[neighbor].iter().any(|o| *o) {
+                            return None;
+                        }
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        return Some(
+            options
+                .iter()
+                .map(|cell| cell.iter().position(|o| *o).unwrap_or(0))
+                .collect(),
+        );
+    }
+
+    fn render(&mut self, patterns: &[Pattern], solution: &[usize]) {
+        let (grid_w, _) = self.grid_size();
+        self.map.tiles = vec![TileType::Wall; self.map.num_tiles as usize];
+
+        for (cell, pattern) in solution.iter().enumerate() {
+            let (cx, cy) = (cell as i32 % grid_w, cell as i32 / grid_w);
+            for y in 0..PATTERN_SIZE {
+                for x in 0..PATTERN_SIZE {
+                    if patterns[*pattern].floor[(y * PATTERN_SIZE + x) as usize] {
+                        let idx = self
+                            .map
+                            .xy_idx(1 + cx * PATTERN_SIZE + x, 1 + cy * PATTERN_SIZE + y);
+                        self.map.tiles[idx] = TileType::Floor;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl MapBuilder for WaveFunctionCollapseBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.source.build_map(rng);
+        let source = self.source.get_map();
+        self.history = self.source.get_snapshot_history();
+        self.history.push(source.tiles.clone());
+
+        let patterns = self.extract_patterns(&source);
+        let compatible: Vec<Vec<Vec<bool>>> = patterns
+            .iter()
+            .map(|a| {
+                (0..DIRECTIONS.len())
+                    .map(|direction| {
+                        let opposite = (direction + 2) % DIRECTIONS.len();
+                        patterns
+                            .iter()
+                            .map(|b| a.edge(direction) == b.edge(opposite))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let center = Point::new(self.map.width / 2, self.map.height / 2);
+        for _attempt in 0..MAX_ATTEMPTS {
+            let solution = match self.solve(&patterns, &compatible, rng) {
+                Some(solution) => solution,
+                None => continue,
+            };
+
+            self.render(&patterns, &solution);
+            self.history.push(self.map.tiles.clone());
+            self.starting_position = nearest_floor(&self.map, center);
+            let exit_idx = cull_unreachable(&mut self.map, self.starting_position);
+
+            if floor_count(&self.map) as i32 >= self.map.num_tiles / 10 {
+                self.map.tiles[exit_idx] = TileType::DownStairs;
+                return;
+            }
+        }
+
+        // Give up and fall back to the level the patterns were learned from,
+        // keeping any entities the source placed on it.
+        let depth = self.map.depth;
+        self.map = source;
+        self.map.depth = depth;
+        self.starting_position = self.source.get_starting_position();
+        self.spawn_list = self.source.get_spawn_list();
+        self.history.push(self.map.tiles.clone());
+    }
+
+    fn get_map(&self) -> Map {
+        return self.map.clone();
+    }
+
+    fn get_starting_position(&self) -> Point {
+        return self.starting_position;
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Vec<TileType>> {
+        return self.history.clone();
+    }
+
+    fn get_spawn_regions(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<usize>> {
+        return voronoi_regions(&self.map, self.starting_position, rng);
+    }
+
+    fn get_spawn_list(&self) -> Vec<(Point, String)> {
+        return self.spawn_list.clone();
+    }
+}
@@ -0,0 +1,129 @@
+use super::{draw_map, Map, TileType};
+use rltk::{Console, Rltk, VirtualKeyCode, RGB};
+
+const MIN_DELAY_MS: f32 = 10.0;
+const MAX_DELAY_MS: f32 = 1280.0;
+const DEFAULT_DELAY_MS: f32 = 80.0;
+
+pub enum MapGenResult {
+    Playing,
+    Finished,
+}
+
+/// Replays the tile snapshots a map builder recorded while generating the
+/// current level.
+pub struct MapGenPlayback {
+    history: Vec<Vec<TileType>>,
+    index: usize,
+    timer: f32,
+    delay_ms: f32,
+    paused: bool,
+}
+
+impl MapGenPlayback {
+    pub fn new() -> MapGenPlayback {
+        return MapGenPlayback {
+            history: Vec::new(),
+            index: 0,
+            timer: 0.0,
+            delay_ms: DEFAULT_DELAY_MS,
+            paused: false,
+        };
+    }
+
+    pub fn reset(&mut self, history: Vec<Vec<TileType>>) {
+        self.history = history;
+        self.index = 0;
+        self.timer = 0.0;
+        self.paused = false;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.history.is_empty();
+    }
+
+    pub fn update(&mut self, ctx: &mut Rltk) -> MapGenResult {
+        let last = self.history.len().saturating_sub(1);
+
+        match ctx.key {
+            None => {}
+            Some(key) => match key {
+                VirtualKeyCode::Space => self.paused = !self.paused,
+                VirtualKeyCode::Right => {
+                    self.paused = true;
+                    self.index = std::cmp::min(self.index + 1, last);
+                }
+                VirtualKeyCode::Left => {
+                    self.paused = true;
+                    self.index = self.index.saturating_sub(1);
+                }
+                VirtualKeyCode::Add | VirtualKeyCode::Equals => {
+                    self.delay_ms = f32::max(self.delay_ms / 2.0, MIN_DELAY_MS);
+                }
+                VirtualKeyCode::Subtract | VirtualKeyCode::Minus => {
+                    self.delay_ms = f32::min(self.delay_ms * 2.0, MAX_DELAY_MS);
+                }
+                VirtualKeyCode::Return | VirtualKeyCode::Escape => {
+                    return MapGenResult::Finished;
+                }
+                _ => {}
+            },
+        }
+
+        if self.paused {
+            return MapGenResult::Playing;
+        }
+
+        self.timer += ctx.frame_time_ms;
+        if self.timer >= self.delay_ms {
+            self.timer = 0.0;
+            if self.index >= last {
+                return MapGenResult::Finished;
+            }
+            self.index += 1;
+        }
+
+        return MapGenResult::Playing;
+    }
+
+    pub fn draw(&self, map: &Map, ctx: &mut Rltk) {
+        if let Some(tiles) = self.history.get(self.index) {
+            let mut snapshot = map.clone();
+            snapshot.tiles = tiles.clone();
+            snapshot.revealed_tiles = vec![true; tiles.len()];
+            snapshot.visible_tiles = vec![true; tiles.len()];
+            draw_map(&snapshot, ctx);
+        }
+
+        ctx.draw_box(
+            0,
+            43,
+            79,
+            6,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+        );
+
+        let status = if self.paused { " (paused)" } else { "" };
+        ctx.print_color(
+            2,
+            44,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            &format!(
+                "Map generation: step {} / {}{}",
+                self.index + 1,
+                self.history.len(),
+                status
+            ),
+        );
+        ctx.print(2, 45, &format!("Delay: {} ms per step", self.delay_ms));
+        ctx.print_color(
+            2,
+            47,
+            RGB::named(rltk::GREY),
+            RGB::named(rltk::BLACK),
+            "Space: pause  Left/Right: step  +/-: speed  Enter: start playing",
+        );
+    }
+}
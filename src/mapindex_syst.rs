@@ -1,4 +1,4 @@
-use super::{BlocksTile, Map, Position};
+use super::{BlocksSight, BlocksTile, Map, Position};
 use rltk::Algorithm2D;
 use specs::prelude::*;
 
@@ -9,13 +9,15 @@ impl<'a> System<'a> for MapIndexingSystem {
         WriteExpect<'a, Map>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, BlocksSight>,
         Entities<'a>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, sight_blockers, entities) = data;
 
         map.populate_blocked();
+        map.populate_opaque();
         map.clear_content_index();
         for (pos, ent) in (&position, &entities).join() {
             let idx = map.point2d_to_index(pos.pt) as usize;
@@ -25,6 +27,10 @@ impl<'a> System<'a> for MapIndexingSystem {
                 map.blocked_tiles[idx] = true;
             }
 
+            if sight_blockers.get(ent).is_some() {
+                map.opaque_tiles[idx] = true;
+            }
+
             map.tile_content[idx].push(ent);
         }
     }
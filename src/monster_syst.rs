@@ -1,4 +1,4 @@
-use super::{Map, MeleeMessage, Monster, Position, RunState, Viewshed};
+use super::{Map, MeleeMessage, Monster, MyTurn, Position, Viewshed};
 use rltk::Algorithm2D;
 use specs::prelude::*;
 
@@ -8,23 +8,24 @@ impl<'a> System<'a> for MonsterAI {
     type SystemData = (
         WriteExpect<'a, Map>,
         ReadExpect<'a, Entity>,
-        ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Monster>,
+        ReadStorage<'a, MyTurn>,
         WriteStorage<'a, MeleeMessage>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, player, runstate, entities, mut vis, mut pos, mon, mut melee_msg) = data;
-        let player_pos = pos.get(*player).unwrap().pt;
-
-        if *runstate != RunState::MonsterTurn {
-            return;
-        }
-
-        for (entity, mut vs, mut pos, _mon) in (&entities, &mut vis, &mut pos, &mon).join() {
+        let (mut map, player, entities, mut vis, mut pos, mon, turns, mut melee_msg) = data;
+        let player_pos = match pos.get(*player) {
+            Some(player_pos) => player_pos.pt,
+            None => return,
+        };
+
+        for (entity, mut vs, mut pos, _mon, _turn) in
+            (&entities, &mut vis, &mut pos, &mon, &turns).join()
+        {
             if vs.visible_tiles.contains(&player_pos) {
                 let distance = rltk::DistanceAlg::Pythagoras.distance2d(pos.pt, player_pos);
                 if distance < 1.5 {
@@ -4,6 +4,7 @@ use rltk::{Algorithm2D, Point, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 
 use super::components::*;
+use super::attributes::carry_capacity;
 use super::game_log::*;
 use super::map::*;
 use super::{RunState, State};
@@ -15,6 +16,11 @@ pub fn move_player(dx: i32, dy: i32, ecs: &mut World) {
     let mut melee = ecs.write_storage::<MeleeMessage>();
     let entities = ecs.entities();
     let combat = ecs.read_storage::<CombatStats>();
+    let mut doors = ecs.write_storage::<Door>();
+    let mut blocks_tile = ecs.write_storage::<BlocksTile>();
+    let mut blocks_sight = ecs.write_storage::<BlocksSight>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
     let map = ecs.fetch::<Map>();
 
     for (entity, _player, pos, vs) in (&entities, &players, &mut position, &mut viewshed).join() {
@@ -28,6 +34,19 @@ pub fn move_player(dx: i32, dy: i32, ecs: &mut World) {
                     .insert(entity, MeleeMessage { target: *content })
                     .expect("Add target failed.");
             }
+
+            if let Some(door) = doors.get_mut(*content) {
+                if !door.open {
+                    door.open = true;
+                    blocks_tile.remove(*content);
+                    blocks_sight.remove(*content);
+                    if let Some(render) = renderables.get_mut(*content) {
+                        render.glyph = rltk::to_cp437('\'');
+                    }
+                    gamelog.add_message("You open the door.".to_string());
+                    vs.dirty = true;
+                }
+            }
         }
 
         if !map.blocked_tiles[dest] {
@@ -57,13 +76,44 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
 
             VirtualKeyCode::G => get_item(&mut gs.ecs),
             VirtualKeyCode::I => return RunState::ShowInventory,
+            VirtualKeyCode::C => return RunState::ShowCharacter,
+            VirtualKeyCode::Period => {
+                if try_stairs(&mut gs.ecs, TileType::DownStairs) {
+                    return RunState::NextLevel;
+                }
+                return RunState::AwaitingInput;
+            }
+            VirtualKeyCode::Comma => {
+                if try_stairs(&mut gs.ecs, TileType::UpStairs) {
+                    return RunState::PreviousLevel;
+                }
+                return RunState::AwaitingInput;
+            }
 
             VirtualKeyCode::Escape => return RunState::Exit,
             _ => return RunState::AwaitingInput,
         },
     }
 
-    return RunState::PlayerTurn;
+    return RunState::Ticking;
+}
+
+fn try_stairs(ecs: &mut World, stairs: TileType) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let idx = map.point2d_to_index(*player_pos) as usize;
+    if map.tiles[idx] == stairs {
+        return true;
+    }
+
+    if stairs == TileType::DownStairs {
+        gamelog.add_message("There is no way down from here.".to_string());
+    } else {
+        gamelog.add_message("There is no way up from here.".to_string());
+    }
+    return false;
 }
 
 fn get_item(ecs: &mut World) {
@@ -81,11 +131,73 @@ fn get_item(ecs: &mut World) {
         }
     }
 
+    let attributes = ecs.read_storage::<Attributes>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let carried = backpack.join().filter(|b| b.owner == *player_entity).count() as i32;
+    let capacity = carry_capacity(attributes.get(*player_entity));
+
     match target_item {
         None => gamelog.add_message("There is nothing here to pick up.".to_string()),
+        Some(_) if carried >= capacity => gamelog.add_message(format!(
+            "Your pack is full ({} / {} items).",
+            carried, capacity
+        )),
         Some(item) => {
             let mut pickup = ecs.write_storage::<ItemPickupMessage>();
             pickup.insert(*player_entity, ItemPickupMessage{ collected_by: *player_entity, item }).expect("Unable to insert want to pickup");
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn use_item(ecs: &mut World, item: Entity) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut use_msg = ecs.write_storage::<UseItemMessage>();
+    use_msg
+        .insert(*player_entity, UseItemMessage { item })
+        .expect("Unable to insert use item message.");
+
+    return RunState::Ticking;
+}
+
+pub fn drop_item(ecs: &mut World, item: Entity) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut drop_msg = ecs.write_storage::<DropItemMessage>();
+    drop_msg
+        .insert(*player_entity, DropItemMessage { item })
+        .expect("Unable to insert drop item message.");
+
+    return RunState::Ticking;
+}
+
+pub fn inspect_item(ecs: &mut World, item: Entity) -> RunState {
+    let names = ecs.read_storage::<Name>();
+    let potions = ecs.read_storage::<Potion>();
+    let melee_bonus = ecs.read_storage::<MeleeBonus>();
+    let defense_bonus = ecs.read_storage::<DefenseBonus>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let name = &names.get(item).unwrap().name;
+    if let Some(potion) = potions.get(item) {
+        gamelog.add_message(format!(
+            "It is a {}. Drinking it restores up to {} hp.",
+            name, potion.heal_amount
+        ));
+    } else if let Some(bonus) = melee_bonus.get(item) {
+        gamelog.add_message(format!(
+            "It is a {}. Wielding it adds {:+} to hit.",
+            name, bonus.atk
+        ));
+    } else if let Some(bonus) = defense_bonus.get(item) {
+        gamelog.add_message(format!(
+            "It is a {}. Wearing it adds {} def.",
+            name, bonus.def
+        ));
+    } else {
+        gamelog.add_message(format!(
+            "It is a {}. Nothing else stands out about it.",
+            name
+        ));
+    }
+
+    return RunState::AwaitingInput;
+}
@@ -0,0 +1,65 @@
+use rltk::RandomNumberGenerator;
+
+pub struct RandomEntry {
+    pub name: String,
+    pub weight: i32,
+}
+
+#[derive(Default)]
+pub struct RandomTable {
+    pub entries: Vec<RandomEntry>,
+    pub total_weight: i32,
+}
+
+impl RandomTable {
+    pub fn new() -> RandomTable {
+        return RandomTable {
+            entries: Vec::new(),
+            total_weight: 0,
+        };
+    }
+
+    pub fn add(&mut self, name: &str, weight: i32) {
+        if weight < 1 {
+            return;
+        }
+
+        match self.entries.iter_mut().find(|e| e.name == name) {
+            Some(entry) => entry.weight += weight,
+            None => self.entries.push(RandomEntry {
+                name: name.to_string(),
+                weight,
+            }),
+        }
+        self.total_weight += weight;
+    }
+
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> Option<String> {
+        if self.total_weight == 0 {
+            return None;
+        }
+
+        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
+        for entry in self.entries.iter() {
+            if roll < entry.weight {
+                return Some(entry.name.clone());
+            }
+            roll -= entry.weight;
+        }
+
+        return None;
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for entry in self.entries.iter() {
+            let chance = entry.weight as f32 / self.total_weight as f32 * 100.0;
+            lines.push(format!(
+                "{:<20} {:>5} {:>6.1}%",
+                entry.name, entry.weight, chance
+            ));
+        }
+
+        return lines;
+    }
+}
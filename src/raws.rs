@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::fs;
+
+use super::attributes::max_hp;
+use super::components::*;
+use super::dice::Dice;
+use super::initiative_syst::NORMAL_SPEED;
+use super::random_table::*;
+use rltk::{Point, RGB};
+use serde::Deserialize;
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RawRenderable {
+    pub glyph: String,
+    pub fg: String,
+    pub bg: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RawStats {
+    pub max_hp: i32,
+    pub atk: i32,
+    pub def: i32,
+    pub damage: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RawMonster {
+    pub name: String,
+    pub renderable: RawRenderable,
+    pub stats: RawStats,
+    pub vision_range: i32,
+    #[serde(default = "default_speed")]
+    pub speed: i32,
+    #[serde(default)]
+    pub attributes: Option<Attributes>,
+    #[serde(default)]
+    pub skills: Option<Skills>,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    #[serde(default)]
+    pub blocks_sight: bool,
+}
+
+fn default_speed() -> i32 {
+    return NORMAL_SPEED;
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RawEquippable {
+    pub slot: String,
+    #[serde(default)]
+    pub melee_bonus: i32,
+    #[serde(default)]
+    pub defense_bonus: i32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RawItem {
+    pub name: String,
+    pub renderable: RawRenderable,
+    pub heal_amount: Option<i32>,
+    pub equippable: Option<RawEquippable>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RawProp {
+    pub name: String,
+    pub renderable: RawRenderable,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    #[serde(default)]
+    pub blocks_sight: bool,
+    #[serde(default)]
+    pub door: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RawSpawnEntry {
+    pub name: String,
+    pub weight: i32,
+    pub min_depth: i32,
+    pub max_depth: i32,
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct Raws {
+    #[serde(default)]
+    pub monsters: Vec<RawMonster>,
+    #[serde(default)]
+    pub items: Vec<RawItem>,
+    #[serde(default)]
+    pub props: Vec<RawProp>,
+    #[serde(default)]
+    pub spawn_table: Vec<RawSpawnEntry>,
+}
+
+#[derive(Clone)]
+enum RawTemplate {
+    Monster(RawMonster),
+    Item(RawItem),
+    Prop(RawProp),
+}
+
+#[derive(Default)]
+pub struct RawMaster {
+    pub raws: Raws,
+    index: HashMap<String, RawTemplate>,
+}
+
+impl RawMaster {
+    pub fn load(path: &str) -> Result<RawMaster, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Unable to read {}: {}", path, e))?;
+        let raws: Raws = serde_json::from_str(&contents)
+            .map_err(|e| format!("Unable to parse {}: {}", path, e))?;
+
+        return RawMaster::from_raws(raws)
+            .map_err(|e| format!("Invalid entity definitions in {}:\n{}", path, e));
+    }
+
+    pub fn from_raws(raws: Raws) -> Result<RawMaster, String> {
+        let mut errors: Vec<String> = Vec::new();
+        let mut index: HashMap<String, RawTemplate> = HashMap::new();
+
+        for monster in raws.monsters.iter() {
+            let ctx = format!("monster '{}'", monster.name);
+            check_renderable(&ctx, &monster.renderable, &mut errors);
+            if monster.stats.max_hp < 1 {
+                errors.push(format!("{}: max_hp must be at least 1", ctx));
+            }
+            if monster.stats.atk < 0 || monster.stats.def < 0 {
+                errors.push(format!("{}: atk and def cannot be negative", ctx));
+            }
+            if let Err(e) = Dice::parse(&monster.stats.damage) {
+                errors.push(format!("{}: {}", ctx, e));
+            }
+            if monster.vision_range < 1 {
+                errors.push(format!("{}: vision_range must be at least 1", ctx));
+            }
+            if monster.speed < 1 {
+                errors.push(format!("{}: speed must be at least 1", ctx));
+            }
+            if let Some(attrs) = &monster.attributes {
+                if attrs.might < 1 || attrs.fitness < 1 || attrs.quickness < 1 || attrs.intelligence < 1
+                {
+                    errors.push(format!("{}: attributes must be at least 1", ctx));
+                }
+            }
+            if let Some(skills) = &monster.skills {
+                if skills.melee < 0 || skills.defense < 0 || skills.magic < 0 {
+                    errors.push(format!("{}: skills cannot be negative", ctx));
+                }
+            }
+            add_template(
+                &mut index,
+                &monster.name,
+                RawTemplate::Monster(monster.clone()),
+                &mut errors,
+            );
+        }
+
+        for item in raws.items.iter() {
+            let ctx = format!("item '{}'", item.name);
+            check_renderable(&ctx, &item.renderable, &mut errors);
+            if let Some(heal_amount) = item.heal_amount {
+                if heal_amount < 1 {
+                    errors.push(format!("{}: heal_amount must be at least 1", ctx));
+                }
+            }
+            if let Some(equippable) = &item.equippable {
+                if let Err(e) = parse_slot(&equippable.slot) {
+                    errors.push(format!("{}: {}", ctx, e));
+                }
+                if item.heal_amount.is_some() {
+                    errors.push(format!("{}: an item cannot be both a potion and equipment", ctx));
+                }
+            }
+            add_template(
+                &mut index,
+                &item.name,
+                RawTemplate::Item(item.clone()),
+                &mut errors,
+            );
+        }
+
+        for prop in raws.props.iter() {
+            let ctx = format!("prop '{}'", prop.name);
+            check_renderable(&ctx, &prop.renderable, &mut errors);
+            add_template(
+                &mut index,
+                &prop.name,
+                RawTemplate::Prop(prop.clone()),
+                &mut errors,
+            );
+        }
+
+        for entry in raws.spawn_table.iter() {
+            let ctx = format!("spawn table entry '{}'", entry.name);
+            if !index.contains_key(&entry.name) {
+                errors.push(format!("{}: no monster, item or prop has this name", ctx));
+            }
+            if entry.weight < 1 {
+                errors.push(format!("{}: weight must be at least 1", ctx));
+            }
+            if entry.min_depth < 1 {
+                errors.push(format!("{}: min_depth must be at least 1", ctx));
+            }
+            if entry.max_depth < entry.min_depth {
+                errors.push(format!("{}: max_depth is less than min_depth", ctx));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+
+        return Ok(RawMaster { raws, index });
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        return self.index.contains_key(name);
+    }
+
+    pub fn spawn_table(&self, depth: i32) -> RandomTable {
+        let mut table = RandomTable::new();
+        for entry in self.raws.spawn_table.iter() {
+            if depth >= entry.min_depth && depth <= entry.max_depth {
+                table.add(&entry.name, entry.weight);
+            }
+        }
+
+        return table;
+    }
+}
+
+fn add_template(
+    index: &mut HashMap<String, RawTemplate>,
+    name: &str,
+    template: RawTemplate,
+    errors: &mut Vec<String>,
+) {
+    if name.trim().is_empty() {
+        errors.push("an entity definition has an empty name".to_string());
+    } else if index.contains_key(name) {
+        errors.push(format!("'{}' is defined more than once", name));
+    } else {
+        index.insert(name.to_string(), template);
+    }
+}
+
+fn check_renderable(ctx: &str, raw: &RawRenderable, errors: &mut Vec<String>) {
+    if let Err(e) = parse_renderable(raw) {
+        errors.push(format!("{}: {}", ctx, e));
+    }
+}
+
+fn parse_renderable(raw: &RawRenderable) -> Result<Renderable, String> {
+    let mut chars = raw.glyph.chars();
+    let glyph = match (chars.next(), chars.next()) {
+        (Some(c), None) => rltk::to_cp437(c),
+        _ => {
+            return Err(format!(
+                "glyph '{}' must be exactly one character",
+                raw.glyph
+            ))
+        }
+    };
+
+    return Ok(Renderable {
+        glyph,
+        fg: parse_color(&raw.fg)?,
+        bg: parse_color(&raw.bg)?,
+    });
+}
+
+fn parse_color(hex: &str) -> Result<RGB, String> {
+    let err = || format!("color '{}' must be in #rrggbb form", hex);
+    if hex.len() != 7 || !hex.is_ascii() || !hex.starts_with('#') {
+        return Err(err());
+    }
+
+    let r = u8::from_str_radix(&hex[1..3], 16).map_err(|_| err())?;
+    let g = u8::from_str_radix(&hex[3..5], 16).map_err(|_| err())?;
+    let b = u8::from_str_radix(&hex[5..7], 16).map_err(|_| err())?;
+
+    return Ok(RGB::from_f32(
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0,
+    ));
+}
+
+fn parse_slot(slot: &str) -> Result<EquipmentSlot, String> {
+    match slot {
+        "Weapon" => return Ok(EquipmentSlot::Weapon),
+        "Armor" => return Ok(EquipmentSlot::Armor),
+        _ => {
+            return Err(format!(
+                "unknown equipment slot '{}' (expected Weapon or Armor)",
+                slot
+            ))
+        }
+    }
+}
+
+pub fn spawn_named(ecs: &mut World, name: &str, pt: Point) -> Option<Entity> {
+    let template = ecs.fetch::<RawMaster>().index.get(name).cloned();
+
+    match template {
+        None => return None,
+        Some(RawTemplate::Monster(monster)) => return Some(spawn_monster(ecs, &monster, pt)),
+        Some(RawTemplate::Item(item)) => return Some(spawn_item(ecs, &item, pt)),
+        Some(RawTemplate::Prop(prop)) => return Some(spawn_prop(ecs, &prop, pt)),
+    }
+}
+
+fn spawn_monster(ecs: &mut World, raw: &RawMonster, pt: Point) -> Entity {
+    let hp = max_hp(raw.stats.max_hp, raw.attributes.as_ref());
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { pt })
+        .with(parse_renderable(&raw.renderable).expect("Raws were not validated."))
+        .marked::<SimpleMarker<SerializeMe>>()
+        .with(Name {
+            name: raw.name.clone(),
+        })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: raw.vision_range,
+            dirty: true,
+        })
+        .with(Monster {})
+        .with(CombatStats {
+            base_hp: raw.stats.max_hp,
+            cur_hp: hp,
+            atk: raw.stats.atk,
+            def: raw.stats.def,
+            damage: Dice::parse(&raw.stats.damage).expect("Raws were not validated."),
+        })
+        .with(Initiative {
+            base_speed: raw.speed,
+            energy: 0,
+        });
+
+    if let Some(attributes) = &raw.attributes {
+        builder = builder.with(attributes.clone());
+    }
+    if let Some(skills) = &raw.skills {
+        builder = builder.with(skills.clone());
+    }
+    if raw.blocks_tile {
+        builder = builder.with(BlocksTile {});
+    }
+    if raw.blocks_sight {
+        builder = builder.with(BlocksSight {});
+    }
+
+    return builder.build();
+}
+
+fn spawn_item(ecs: &mut World, raw: &RawItem, pt: Point) -> Entity {
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { pt })
+        .with(parse_renderable(&raw.renderable).expect("Raws were not validated."))
+        .marked::<SimpleMarker<SerializeMe>>()
+        .with(Name {
+            name: raw.name.clone(),
+        })
+        .with(Item {});
+
+    if let Some(heal_amount) = raw.heal_amount {
+        builder = builder.with(Potion { heal_amount });
+    }
+    if let Some(equippable) = &raw.equippable {
+        builder = builder.with(Equippable {
+            slot: parse_slot(&equippable.slot).expect("Raws were not validated."),
+        });
+        if equippable.melee_bonus != 0 {
+            builder = builder.with(MeleeBonus {
+                atk: equippable.melee_bonus,
+            });
+        }
+        if equippable.defense_bonus != 0 {
+            builder = builder.with(DefenseBonus {
+                def: equippable.defense_bonus,
+            });
+        }
+    }
+
+    return builder.build();
+}
+
+fn spawn_prop(ecs: &mut World, raw: &RawProp, pt: Point) -> Entity {
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { pt })
+        .with(parse_renderable(&raw.renderable).expect("Raws were not validated."))
+        .marked::<SimpleMarker<SerializeMe>>()
+        .with(Name {
+            name: raw.name.clone(),
+        });
+
+    if raw.blocks_tile {
+        builder = builder.with(BlocksTile {});
+    }
+    if raw.blocks_sight {
+        builder = builder.with(BlocksSight {});
+    }
+    if raw.door {
+        builder = builder.with(Door { open: false });
+    }
+
+    return builder.build();
+}
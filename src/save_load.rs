@@ -0,0 +1,240 @@
+use std::fs;
+
+use super::components::*;
+use super::{DungeonMaster, GameLog, GameStats, Map, RunState};
+use rltk::Point;
+use serde::{Deserialize, Serialize};
+use specs::error::NoError;
+use specs::prelude::*;
+use specs::saveload::{
+    DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+};
+
+const SAVE_PATH: &str = "./savegame.json";
+const SAVE_VERSION: u32 = 9;
+
+#[derive(Serialize, Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+macro_rules! serialize_individually {
+    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
+            &( $ecs.read_storage::<$type>(), ),
+            &$data.0,
+            &$data.1,
+            &mut $ser,
+        )
+        .map_err(|e| format!("Unable to save {}: {}", stringify!($type), e))?;
+        )*
+    };
+}
+
+macro_rules! deserialize_individually {
+    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        DeserializeComponents::<NoError, _>::deserialize(
+            &mut ( &mut $ecs.write_storage::<$type>(), ),
+            &mut $data.0,
+            &mut $data.1,
+            &mut $data.2,
+            &mut $de,
+        )
+        .map_err(|e| format!("Save file is corrupt ({}): {}", stringify!($type), e))?;
+        )*
+    };
+}
+
+pub fn save_game(ecs: &mut World, runstate: RunState) -> Result<(), String> {
+    let helper = SerializationHelper {
+        map: (*ecs.fetch::<Map>()).clone(),
+        log: (*ecs.fetch::<GameLog>()).clone(),
+        stats: (*ecs.fetch::<GameStats>()).clone(),
+        dungeon: (*ecs.fetch::<DungeonMaster>()).clone(),
+        runstate,
+    };
+    let helper_entity = ecs
+        .create_entity()
+        .with(helper)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    let result = write_save(ecs);
+
+    ecs.delete_entity(helper_entity)
+        .expect("Unable to delete serialization helper.");
+
+    return result;
+}
+
+fn write_save(ecs: &World) -> Result<(), String> {
+    let header = serde_json::to_string(&SaveHeader {
+        version: SAVE_VERSION,
+    })
+    .map_err(|e| format!("Unable to save header: {}", e))?;
+
+    let mut body: Vec<u8> = Vec::new();
+    {
+        let data = (
+            ecs.entities(),
+            ecs.read_storage::<SimpleMarker<SerializeMe>>(),
+        );
+        let mut serializer = serde_json::Serializer::new(&mut body);
+        serialize_individually!(
+            ecs,
+            serializer,
+            data,
+            Position,
+            OtherLevelPosition,
+            Renderable,
+            Name,
+            Player,
+            Monster,
+            CombatStats,
+            MeleeMessage,
+            DamageMessage,
+            Viewshed,
+            BlocksTile,
+            BlocksSight,
+            Item,
+            Potion,
+            ItemPickupMessage,
+            InBackpack,
+            UseItemMessage,
+            DropItemMessage,
+            Equippable,
+            Equipped,
+            MeleeBonus,
+            DefenseBonus,
+            Attributes,
+            Skills,
+            Door,
+            Initiative,
+            SerializationHelper
+        );
+    }
+
+    let mut contents = header.into_bytes();
+    contents.push(b'\n');
+    contents.append(&mut body);
+
+    return fs::write(SAVE_PATH, contents)
+        .map_err(|e| format!("Unable to write {}: {}", SAVE_PATH, e));
+}
+
+pub fn save_exists() -> bool {
+    return std::path::Path::new(SAVE_PATH).exists();
+}
+
+pub fn delete_save() -> Result<(), String> {
+    if !save_exists() {
+        return Ok(());
+    }
+
+    return fs::remove_file(SAVE_PATH)
+        .map_err(|e| format!("Unable to delete {}: {}", SAVE_PATH, e));
+}
+
+pub fn load_game() -> Result<World, String> {
+    let contents = fs::read_to_string(SAVE_PATH)
+        .map_err(|e| format!("Unable to read {}: {}", SAVE_PATH, e))?;
+
+    let mut parts = contents.splitn(2, '\n');
+    let header: SaveHeader = serde_json::from_str(parts.next().unwrap_or(""))
+        .map_err(|_| "Save file is corrupt: missing version header.".to_string())?;
+    if header.version != SAVE_VERSION {
+        return Err(format!(
+            "Save file version {} is not supported (expected version {}).",
+            header.version, SAVE_VERSION
+        ));
+    }
+    let body = parts.next().unwrap_or("");
+
+    let mut ecs = World::new();
+    register_components(&mut ecs);
+    {
+        let mut de = serde_json::Deserializer::from_str(body);
+        let mut data = (
+            &mut ecs.entities(),
+            &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(),
+            &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+        );
+        deserialize_individually!(
+            ecs,
+            de,
+            data,
+            Position,
+            OtherLevelPosition,
+            Renderable,
+            Name,
+            Player,
+            Monster,
+            CombatStats,
+            MeleeMessage,
+            DamageMessage,
+            Viewshed,
+            BlocksTile,
+            BlocksSight,
+            Item,
+            Potion,
+            ItemPickupMessage,
+            InBackpack,
+            UseItemMessage,
+            DropItemMessage,
+            Equippable,
+            Equipped,
+            MeleeBonus,
+            DefenseBonus,
+            Attributes,
+            Skills,
+            Door,
+            Initiative,
+            SerializationHelper
+        );
+    }
+
+    let mut helper_data: Option<(Entity, SerializationHelper)> = None;
+    let mut player_entity: Option<(Entity, Point)> = None;
+    {
+        let entities = ecs.entities();
+        let helpers = ecs.read_storage::<SerializationHelper>();
+        let players = ecs.read_storage::<Player>();
+        let positions = ecs.read_storage::<Position>();
+
+        for (entity, helper) in (&entities, &helpers).join() {
+            helper_data = Some((entity, helper.clone()));
+        }
+
+        for (entity, _player, pos) in (&entities, &players, &positions).join() {
+            player_entity = Some((entity, pos.pt));
+        }
+    }
+
+    match helper_data {
+        None => return Err("Save file is corrupt: no game state found.".to_string()),
+        Some((entity, helper)) => {
+            let mut map = helper.map;
+            map.tile_content = vec![Vec::new(); map.num_tiles as usize];
+            map.populate_opaque();
+            ecs.insert(map);
+            ecs.insert(helper.log);
+            ecs.insert(helper.stats);
+            ecs.insert(helper.dungeon);
+            ecs.insert(helper.runstate);
+            ecs.delete_entity(entity)
+                .expect("Unable to delete serialization helper.");
+        }
+    }
+
+    match player_entity {
+        None => return Err("Save file is corrupt: no player found.".to_string()),
+        Some((entity, pt)) => {
+            ecs.insert(entity);
+            ecs.insert(pt);
+        }
+    }
+
+    return Ok(ecs);
+}